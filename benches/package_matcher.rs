@@ -0,0 +1,74 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion, SamplingMode};
+
+use guarding_core::domain::code_file::CodeFile;
+use guarding_core::rule_executor::package_matcher::{is_package_match, PackageMatcher};
+use guarding_core::rule_executor::RuleExecutor;
+use guarding_parser::parser;
+
+fn synthetic_models(files: usize, imports: usize) -> Vec<CodeFile> {
+    (0..files).map(|i| {
+        CodeFile {
+            path: format!("src/module{}/File{}.java", i % 50, i),
+            package: format!("com.company.module{}.layer{}", i % 50, i % 7),
+            imports: (0..imports)
+                .map(|j| format!("com.company.module{}.layer{}.Type{}", (i + j) % 50, j % 7, j))
+                .collect(),
+            ..Default::default()
+        }
+    }).collect()
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    // compiles a regex for each import, so it's compared with reusing a matcher on a small model
+    let small_models = synthetic_models(10, 10);
+
+    let mut group = c.benchmark_group("package_matcher on 10 files");
+    group.sample_size(10);
+    group.sampling_mode(SamplingMode::Flat);
+
+    group.bench_function("is_package_match per import", |b| b.iter(|| {
+        small_models.iter()
+            .flat_map(|file| file.imports.iter())
+            .filter(|imp| is_package_match("..layer3..".to_string(), imp))
+            .count()
+    }));
+
+    group.bench_function("PackageMatcher reused", |b| b.iter(|| {
+        let matcher = PackageMatcher::new("..layer3..").unwrap();
+        small_models.iter()
+            .flat_map(|file| file.imports.iter())
+            .filter(|imp| matcher.is_match(imp))
+            .count()
+    }));
+
+    group.finish();
+
+    let models = synthetic_models(5000, 20);
+
+    let mut group = c.benchmark_group("package_matcher on 5000 files");
+    group.sample_size(10);
+
+    group.bench_function("PackageMatcher reused", |b| b.iter(|| {
+        let matcher = PackageMatcher::new("..layer3..").unwrap();
+        models.iter()
+            .flat_map(|file| file.imports.iter())
+            .filter(|imp| matcher.is_match(imp))
+            .count()
+    }));
+
+    group.finish();
+
+    let rules = parser::parse("class(\"..layer3..\") only accessed([\"..layer2..\", \"..layer4..\"]);").unwrap();
+    c.bench_function("accessed rule on 5000 files", |b| b.iter_batched(
+        || (models.clone(), rules.clone()),
+        |(models, rules)| {
+            let mut executor = RuleExecutor::new(black_box(models), rules);
+            executor.run();
+            executor.errors.len()
+        },
+        BatchSize::LargeInput,
+    ));
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -7,6 +7,15 @@ pub struct CodeAnnotation {
     pub key_values: Vec<AnnotationKeyValue>
 }
 
+impl CodeAnnotation {
+    pub fn new(name: &str) -> CodeAnnotation {
+        CodeAnnotation {
+            name: name.to_string(),
+            key_values: vec![]
+        }
+    }
+}
+
 #[repr(C)]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct AnnotationKeyValue {
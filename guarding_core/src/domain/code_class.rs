@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 
+use crate::domain::code_annotation::CodeAnnotation;
 use crate::domain::code_function::CodeFunction;
 use crate::domain::CodePoint;
 
@@ -10,6 +11,17 @@ pub struct CodeClass {
     pub package: String,
     pub extends: Vec<String>,
     pub implements: Vec<String>,
+    #[serde(default)]
+    pub kind: ClassKind,
+    /// interface, trait or abstract class
+    #[serde(default)]
+    pub is_abstract: bool,
+    /// visibility modifier as written, like `pub(crate)` in Rust, empty if private or not recorded
+    #[serde(default)]
+    pub visibility: String,
+    /// annotations in Java, attributes in C# or decorators in TypeScript
+    #[serde(default)]
+    pub annotations: Vec<CodeAnnotation>,
     pub constant: Vec<ClassConstant>,
     pub functions: Vec<CodeFunction>,
     pub start: CodePoint,
@@ -23,6 +35,10 @@ impl Default for CodeClass {
             package: "".to_string(),
             extends: vec![],
             implements: vec![],
+            kind: ClassKind::Class,
+            is_abstract: false,
+            visibility: "".to_string(),
+            annotations: vec![],
             constant: vec![],
             functions: vec![],
             start: Default::default(),
@@ -31,9 +47,33 @@ impl Default for CodeClass {
     }
 }
 
+impl CodeClass {
+    /// interfaces, traits and annotation types are kept for metrics, but not selected by
+    /// `class(...)` rules
+    pub fn is_interface(&self) -> bool {
+        matches!(self.kind, ClassKind::Interface | ClassKind::Annotation)
+    }
+}
+
 #[repr(C)]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ClassConstant {
     pub name: String,
     pub typ: String,
+    /// value can't be changed, like `static final` in Java
+    #[serde(default)]
+    pub is_constant: bool,
+}
+
+/// kind of type declaration, languages without the distinction keep `Class`
+#[repr(C)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ClassKind {
+    #[default]
+    Class,
+    Interface,
+    Enum,
+    Record,
+    Annotation,
 }
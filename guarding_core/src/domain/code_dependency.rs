@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+/// An import after resolving, in the same dotted form for all languages.
+///
+/// `crate::domain::CodeFile` in crate `guarding_core` becomes `guarding_core.domain.CodeFile`,
+/// `import './say.js'` in `src/app/main.js` becomes `src.app.say`.
+#[repr(C)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CodeDependency {
+    /// import text as written in source code
+    pub source: String,
+    /// dotted name of the imported element
+    pub name: String,
+    /// dotted package (or module) of the imported element
+    pub package: String,
+    /// path of target file, when the import points into current project
+    pub file: Option<String>,
+    /// depends on every element of `package`, like `java.util.*`
+    #[serde(default)]
+    pub is_wildcard: bool,
+}
+
+impl Default for CodeDependency {
+    fn default() -> Self {
+        CodeDependency {
+            source: "".to_string(),
+            name: "".to_string(),
+            package: "".to_string(),
+            file: None,
+            is_wildcard: false,
+        }
+    }
+}
@@ -2,6 +2,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::domain::code_function::CodeFunction;
 use crate::domain::code_class::CodeClass;
+use crate::domain::code_dependency::CodeDependency;
+use crate::domain::code_import::CodeImport;
 
 #[repr(C)]
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -10,8 +12,20 @@ pub struct CodeFile {
     pub path: String,
     pub package: String,
     pub imports: Vec<String>,
+    /// structured form of `imports`, for languages which record it: Java, Rust, TypeScript and Go
+    #[serde(default)]
+    pub code_imports: Vec<CodeImport>,
+    /// imports resolved by `ImportResolver`, empty if models aren't resolved
+    #[serde(default)]
+    pub dependencies: Vec<CodeDependency>,
     pub classes: Vec<CodeClass>,
     pub functions: Vec<CodeFunction>,
+    /// lines of text
+    #[serde(default)]
+    pub lines: usize,
+    /// lines of code, blank and comment lines are skipped
+    #[serde(default)]
+    pub loc: usize,
 }
 
 impl Default for CodeFile {
@@ -21,8 +35,12 @@ impl Default for CodeFile {
             path: "".to_string(),
             package: "".to_string(),
             imports: vec![],
+            code_imports: vec![],
+            dependencies: vec![],
             classes: vec![],
             functions: vec![],
+            lines: 0,
+            loc: 0,
         }
     }
 }
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 
+use crate::domain::code_annotation::CodeAnnotation;
 use crate::domain::CodePoint;
 use crate::domain::Location;
 
@@ -9,6 +10,17 @@ pub struct CodeFunction {
     pub name: String,
     // todo: thinking in access
     pub vars: Vec<String>,
+    #[serde(default)]
+    pub annotations: Vec<CodeAnnotation>,
+    /// cyclomatic complexity, `0` if not measured
+    #[serde(default)]
+    pub complexity: usize,
+    /// max depth of nested branches and loops
+    #[serde(default)]
+    pub nesting: usize,
+    /// visibility modifier as written, like `pub(crate)` in Rust, empty if private or not recorded
+    #[serde(default)]
+    pub visibility: String,
     pub start: CodePoint,
     pub end: CodePoint
 }
@@ -18,6 +30,10 @@ impl Default for CodeFunction {
         CodeFunction {
             name: "".to_string(),
             vars: vec![],
+            annotations: vec![],
+            complexity: 0,
+            nesting: 0,
+            visibility: "".to_string(),
             start: Default::default(),
             end: Default::default()
         }
@@ -1,9 +1,55 @@
 use serde::{Deserialize, Serialize};
 
+/// An import as written in source code, with the details which a plain import string loses.
+///
+/// `import static org.junit.Assert.*;` is `name` = `org.junit.Assert`, `import` = `org.junit.Assert.*`.
 #[repr(C)]
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct CodeImport {
+    /// dotted name of imported element, the package or class of a wildcard import. the module
+    /// specifier without quotes in JavaScript and TypeScript, like `./user`
     pub name: String,
+    /// import text as in `CodeFile::imports`, like `java.util.*`
     pub import: String,
+    /// text of the import statement
     pub source: String,
+    /// `import static` in Java
+    #[serde(default)]
+    pub is_static: bool,
+    /// imports every element of `name`
+    #[serde(default)]
+    pub is_wildcard: bool,
+    /// local name of import, like `Svc` of `import a.Service as Svc`
+    #[serde(default)]
+    pub alias: Option<String>,
+    /// exported again, like `pub use` in Rust
+    #[serde(default)]
+    pub is_reexport: bool,
+    /// only types are imported, like `import type { User } from './user'` in TypeScript
+    #[serde(default)]
+    pub is_type_only: bool,
+    /// row of import statement
+    #[serde(default)]
+    pub line: usize,
+    /// dotted path of the inline module which contains the import, like `tests` of
+    /// `mod tests { use super::*; }` in Rust, empty at file level
+    #[serde(default)]
+    pub module: String,
+}
+
+impl Default for CodeImport {
+    fn default() -> Self {
+        CodeImport {
+            name: "".to_string(),
+            import: "".to_string(),
+            source: "".to_string(),
+            is_static: false,
+            is_wildcard: false,
+            alias: None,
+            is_reexport: false,
+            is_type_only: false,
+            line: 0,
+            module: "".to_string(),
+        }
+    }
 }
@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use code_class::CodeClass;
 
 pub mod code_import;
+pub mod code_dependency;
 pub mod code_file;
 pub mod code_package;
 pub mod code_module;
@@ -28,7 +29,7 @@ pub trait Location {
 }
 
 #[repr(C)]
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct CodePoint {
     pub row: usize,
     pub column: usize
@@ -1,2 +1,3 @@
 pub mod rule_executor;
 pub mod domain;
+pub mod metrics;
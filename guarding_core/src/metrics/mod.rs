@@ -0,0 +1,3 @@
+pub use package_metrics::PackageMetrics;
+
+pub mod package_metrics;
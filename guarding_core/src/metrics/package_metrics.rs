@@ -0,0 +1,154 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::code_file::CodeFile;
+
+/// Package metrics of Robert C. Martin.
+///
+/// Only packages of current project are counted, imports of libraries don't change coupling.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PackageMetrics {
+    pub package: String,
+    /// afferent coupling, packages which depend on this package
+    pub ca: usize,
+    /// efferent coupling, packages which this package depends on
+    pub ce: usize,
+    /// `Ce / (Ca + Ce)`, `0` for a package without coupling
+    pub instability: f64,
+    /// abstract classes / classes, `0` for a package without classes
+    pub abstractness: f64,
+    /// distance from the main sequence, `|A + I - 1|`
+    pub distance: f64,
+    pub classes: usize,
+    pub abstract_classes: usize,
+}
+
+impl PackageMetrics {
+    /// metrics of every package in `models`, ordered by package name
+    pub fn from_models(models: &[CodeFile]) -> Vec<PackageMetrics> {
+        let packages: BTreeSet<&str> = models.iter()
+            .map(|file| file.package.as_str())
+            .collect();
+
+        let mut efferent: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        let mut afferent: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        let mut classes: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+
+        for file in models {
+            let source = file.package.as_str();
+            for target in dependent_packages(file) {
+                if target == source {
+                    continue;
+                }
+
+                if let Some(target) = packages.get(target) {
+                    efferent.entry(source).or_default().insert(target);
+                    afferent.entry(target).or_default().insert(source);
+                }
+            }
+
+            let count = classes.entry(source).or_default();
+            count.0 += file.classes.len();
+            count.1 += file.classes.iter().filter(|class| class.is_abstract).count();
+        }
+
+        packages.iter()
+            .map(|package| {
+                let ca = afferent.get(package).map_or(0, |set| set.len());
+                let ce = efferent.get(package).map_or(0, |set| set.len());
+                let (total, abstracts) = classes.get(package).cloned().unwrap_or_default();
+
+                PackageMetrics::new(package, ca, ce, total, abstracts)
+            })
+            .collect()
+    }
+
+    pub fn new(package: &str, ca: usize, ce: usize, classes: usize, abstract_classes: usize) -> PackageMetrics {
+        let instability = if ca + ce == 0 { 0.0 } else { ce as f64 / (ca + ce) as f64 };
+        let abstractness = if classes == 0 { 0.0 } else { abstract_classes as f64 / classes as f64 };
+
+        PackageMetrics {
+            package: package.to_string(),
+            ca,
+            ce,
+            instability,
+            abstractness,
+            distance: (abstractness + instability - 1.0).abs(),
+            classes,
+            abstract_classes,
+        }
+    }
+
+    /// value of a metric by DSL property name, like `instability`
+    pub fn value(&self, property: &str) -> Option<f64> {
+        match property {
+            "ca" => Some(self.ca as f64),
+            "ce" => Some(self.ce as f64),
+            "instability" => Some(self.instability),
+            "abstractness" => Some(self.abstractness),
+            "distance" => Some(self.distance),
+            _ => None
+        }
+    }
+}
+
+/// packages of imports, resolved dependencies are used when they exist
+fn dependent_packages(file: &CodeFile) -> Vec<&str> {
+    if !file.dependencies.is_empty() {
+        return file.dependencies.iter()
+            .map(|dep| dep.package.as_str())
+            .collect();
+    }
+
+    file.imports.iter()
+        .filter_map(|import| import.rsplit_once('.').map(|(package, _)| package))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::code_class::CodeClass;
+    use crate::domain::code_file::CodeFile;
+    use crate::metrics::PackageMetrics;
+
+    fn file(package: &str, imports: Vec<&str>, classes: Vec<bool>) -> CodeFile {
+        CodeFile {
+            package: package.to_string(),
+            imports: imports.iter().map(|s| s.to_string()).collect(),
+            classes: classes.iter()
+                .map(|is_abstract| CodeClass { is_abstract: *is_abstract, ..Default::default() })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn should_calculate_coupling() {
+        let models = vec![
+            file("app.controller", vec!["app.service.UserService", "java.util.List"], vec![false]),
+            file("app.service", vec!["app.domain.User"], vec![true, false]),
+            file("app.domain", vec![], vec![true]),
+        ];
+
+        let metrics = PackageMetrics::from_models(&models);
+        let names: Vec<&str> = metrics.iter().map(|m| m.package.as_str()).collect();
+        assert_eq!(vec!["app.controller", "app.domain", "app.service"], names);
+
+        let controller = &metrics[0];
+        assert_eq!((0, 1), (controller.ca, controller.ce));
+        assert_eq!(1.0, controller.instability);
+        assert_eq!(0.0, controller.distance);
+
+        let domain = &metrics[1];
+        assert_eq!((1, 0), (domain.ca, domain.ce));
+        assert_eq!(0.0, domain.instability);
+        assert_eq!(1.0, domain.abstractness);
+        assert_eq!(0.0, domain.distance);
+
+        let service = &metrics[2];
+        assert_eq!(0.5, service.instability);
+        assert_eq!(0.5, service.abstractness);
+        assert_eq!(Some(0.0), service.value("distance"));
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::{Component, Path};
+
+use serde::{Deserialize, Serialize};
+
+use guarding_parser::ast::GuardRule;
+
+use crate::rule_executor::rule_error::{ElementKind, RuleErrorMsg, ViolationItem};
+use crate::rule_executor::rule_report;
+
+/// A violation which is accepted, like `FreezingArchRule` in ArchUnit.
+///
+/// Entries don't contain line numbers, so moving code around won't create new violations.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BaselineEntry {
+    /// rule origin with whitespaces collapsed, like `class("..service..") only accessed("..controller..");`
+    pub rule: String,
+    /// `None` for rules without elements, like `package(".")::file.len should < 20;`
+    pub kind: Option<ElementKind>,
+    pub name: String,
+    /// relative to the scanned code dir with `/` separators, so the baseline can be shared
+    /// between checkouts
+    pub path: String,
+    pub import: Option<String>,
+}
+
+impl BaselineEntry {
+    fn new(rule: &str, item: Option<&ViolationItem>, code_dir: &Path) -> BaselineEntry {
+        match item {
+            Some(item) => BaselineEntry {
+                rule: rule.to_string(),
+                kind: Some(item.kind.clone()),
+                name: item.name.clone(),
+                path: relative_path(&item.path, code_dir),
+                import: item.import.clone(),
+            },
+            None => BaselineEntry {
+                rule: rule.to_string(),
+                kind: None,
+                name: "".to_string(),
+                path: "".to_string(),
+                import: None,
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Baseline {
+    pub entries: BTreeSet<BaselineEntry>,
+}
+
+impl Baseline {
+    /// record all violations of `errors`, `rules` are the rules which `errors` come from and
+    /// `code_dir` is the scanned dir
+    pub fn from_errors(rules: &[GuardRule], errors: &[RuleErrorMsg], code_dir: &Path) -> Baseline {
+        let mut entries = BTreeSet::new();
+        for error in errors {
+            let rule = rule_id(rules, error);
+            if error.items.is_empty() {
+                entries.insert(BaselineEntry::new(&rule, None, code_dir));
+            }
+
+            for item in &error.items {
+                entries.insert(BaselineEntry::new(&rule, Some(item), code_dir));
+            }
+        }
+
+        Baseline { entries }
+    }
+
+    pub fn load(path: &Path) -> io::Result<Baseline> {
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        fs::write(path, content)
+    }
+
+    pub fn contains(&self, entry: &BaselineEntry) -> bool {
+        self.entries.contains(entry)
+    }
+
+    /// keep only violations which are not in baseline, errors without new items are dropped
+    pub fn filter_new(&self, rules: &[GuardRule], errors: Vec<RuleErrorMsg>, code_dir: &Path) -> Vec<RuleErrorMsg> {
+        errors.into_iter()
+            .filter_map(|mut error| {
+                let rule = rule_id(rules, &error);
+                if error.items.is_empty() {
+                    return if self.contains(&BaselineEntry::new(&rule, None, code_dir)) {
+                        None
+                    } else {
+                        Some(error)
+                    };
+                }
+
+                error.items.retain(|item| !self.contains(&BaselineEntry::new(&rule, Some(item), code_dir)));
+                if error.items.is_empty() {
+                    None
+                } else {
+                    Some(error)
+                }
+            })
+            .collect()
+    }
+}
+
+/// `src/Lexer.java` of `code/src/Lexer.java` in `code`, paths outside of `code_dir` are kept
+fn relative_path(path: &str, code_dir: &Path) -> String {
+    let path = Path::new(path);
+    let relative = path.strip_prefix(code_dir).unwrap_or(path);
+
+    relative.components()
+        .filter(|component| !matches!(component, Component::CurDir))
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// id of the rule which reports `error`
+fn rule_id(rules: &[GuardRule], error: &RuleErrorMsg) -> String {
+    match rules.get(error.rule_index) {
+        Some(rule) => rule_report::rule_id(rule, error.rule_index),
+        None => format!("#{}", error.rule_index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use guarding_parser::parser;
+
+    use crate::domain::code_class::CodeClass;
+    use crate::domain::code_file::CodeFile;
+    use crate::rule_executor::baseline::Baseline;
+    use crate::rule_executor::rule_error::{MismatchType, RuleErrorMsg, ViolationItem};
+
+    fn class_item(dir: &str, name: &str, row: usize) -> ViolationItem {
+        let file = CodeFile {
+            path: format!("{}/src/{}.java", dir, name),
+            ..Default::default()
+        };
+
+        let mut class = CodeClass {
+            package: "com.phodal".to_string(),
+            name: name.to_string(),
+            ..Default::default()
+        };
+        class.start.row = row;
+
+        ViolationItem::class(&file, &class)
+    }
+
+    fn name_error(items: Vec<ViolationItem>) -> RuleErrorMsg {
+        let mut error = RuleErrorMsg::new(MismatchType::FileName, 0);
+        error.items = items;
+        error
+    }
+
+    #[test]
+    fn should_only_keep_new_violations() {
+        let rules = parser::parse("class(\".\")::name should endsWith \"Parser\";").unwrap();
+        let baseline = Baseline::from_errors(&rules, &[name_error(vec![class_item("code", "Lexer", 1)])], Path::new("code"));
+
+        let errors = vec![name_error(vec![class_item("code", "Lexer", 1), class_item("code", "Token", 8)])];
+        let errors = baseline.filter_new(&rules, errors, Path::new("code"));
+
+        assert_eq!(1, errors.len());
+        assert_eq!(1, errors[0].items.len());
+        assert_eq!("com.phodal.Token", errors[0].items[0].name);
+    }
+
+    #[test]
+    fn should_ignore_line_changes_and_rule_format() {
+        let old_rules = parser::parse("class(\".\")::name   should endsWith \"Parser\";").unwrap();
+        let baseline = Baseline::from_errors(&old_rules, &[name_error(vec![class_item("code", "Lexer", 1)])], Path::new("code"));
+
+        let rules = parser::parse("class(\".\")::name should endsWith \"Parser\";").unwrap();
+        let errors = baseline.filter_new(&rules, vec![name_error(vec![class_item("code", "Lexer", 30)])], Path::new("code"));
+
+        assert_eq!(0, errors.len());
+    }
+
+    #[test]
+    fn should_accept_rule_without_items() {
+        let rules = parser::parse("package(\".\")::file.len should < 2;").unwrap();
+        let error = RuleErrorMsg::new(MismatchType::FileSize, 0);
+        let baseline = Baseline::from_errors(&rules, std::slice::from_ref(&error), Path::new("code"));
+
+        assert_eq!(0, baseline.filter_new(&rules, vec![error], Path::new("code")).len());
+
+        let other_rules = parser::parse("package(\".\")::file.len should < 1;").unwrap();
+        let error = RuleErrorMsg::new(MismatchType::FileSize, 0);
+        assert_eq!(1, baseline.filter_new(&other_rules, vec![error], Path::new("code")).len());
+    }
+
+    #[test]
+    fn should_record_path_relative_to_code_dir() {
+        let rules = parser::parse("class(\".\")::name should endsWith \"Parser\";").unwrap();
+        let baseline = Baseline::from_errors(&rules, &[name_error(vec![class_item("/home/ci/app", "Lexer", 1)])], Path::new("/home/ci/app"));
+        let entry = baseline.entries.iter().next().unwrap();
+        assert_eq!("src/Lexer.java", entry.path);
+
+        let errors = vec![name_error(vec![class_item("./app", "Lexer", 1)])];
+        assert_eq!(0, baseline.filter_new(&rules, errors, Path::new("./app")).len());
+    }
+}
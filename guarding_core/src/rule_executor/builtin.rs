@@ -0,0 +1,540 @@
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use guarding_parser::ast::{GuardRule, Number, Operator, RuleAssert, RuleLevel, RuleScope};
+
+use crate::domain::code_class::CodeClass;
+use crate::domain::code_file::CodeFile;
+use crate::domain::code_function::CodeFunction;
+use crate::metrics::PackageMetrics;
+use crate::rule_executor::evaluator::{EvaluatorRegistry, RuleEvaluator};
+use crate::rule_executor::package_matcher::PackageMatcher;
+use crate::rule_executor::rule_context::RuleContext;
+use crate::rule_executor::rule_error::{MismatchType, RuleErrorMsg, ViolationItem};
+
+const COMPARISON_OPS: [Operator; 6] = [Operator::Gt, Operator::Gte, Operator::Lt, Operator::Lte, Operator::Eq, Operator::Ineq];
+const PACKAGE_METRICS: [&str; 5] = ["ca", "ce", "instability", "abstractness", "distance"];
+const NAMING_PROPERTIES: [(RuleLevel, &str); 10] = [
+    (RuleLevel::Class, "name"),
+    (RuleLevel::Class, "function.name"),
+    (RuleLevel::Class, "field.name"),
+    (RuleLevel::Class, "constant.name"),
+    (RuleLevel::Struct, "name"),
+    (RuleLevel::Struct, "function.name"),
+    (RuleLevel::Struct, "field.name"),
+    (RuleLevel::Struct, "constant.name"),
+    (RuleLevel::Function, "name"),
+    (RuleLevel::File, "name"),
+];
+
+/// property name and how to get the value of an element
+type Metric<T> = (&'static str, fn(&T) -> usize);
+
+const FUNCTION_METRICS: [Metric<CodeFunction>; 3] = [
+    ("complexity", |func| func.complexity),
+    ("nesting", |func| func.nesting),
+    ("lines", |func| func.end.row - func.start.row + 1),
+];
+const CLASS_METRICS: [Metric<CodeClass>; 3] = [
+    ("lines", |class| class.end.row - class.start.row + 1),
+    ("function.len", |class| class.functions.len()),
+    ("field.len", |class| class.constant.len()),
+];
+const FILE_METRICS: [Metric<CodeFile>; 2] = [
+    ("lines", |file| file.lines),
+    ("loc", |file| file.loc),
+];
+const STRING_OPS: [Operator; 3] = [Operator::StartsWith, Operator::Endswith, Operator::Contains];
+
+pub fn register_builtin(registry: &mut EvaluatorRegistry) {
+    let file_count: Arc<dyn RuleEvaluator> = Arc::new(FileCountEvaluator);
+    let class_count: Arc<dyn RuleEvaluator> = Arc::new(ClassCountEvaluator);
+    let class_name: Arc<dyn RuleEvaluator> = Arc::new(ClassNameEvaluator);
+    let package_metric: Arc<dyn RuleEvaluator> = Arc::new(PackageMetricEvaluator);
+
+    for op in COMPARISON_OPS.iter() {
+        registry.register_shared(RuleLevel::Package, "len", op.clone(), file_count.clone());
+        registry.register_shared(RuleLevel::Package, "file.len", op.clone(), file_count.clone());
+
+        registry.register_shared(RuleLevel::Class, "len", op.clone(), class_count.clone());
+        registry.register_shared(RuleLevel::Struct, "len", op.clone(), class_count.clone());
+
+        for metric in PACKAGE_METRICS.iter() {
+            registry.register_shared(RuleLevel::Package, metric, op.clone(), package_metric.clone());
+        }
+
+        for (metric, value) in FUNCTION_METRICS.iter() {
+            registry.register(RuleLevel::Function, metric, op.clone(), FunctionMetricEvaluator { value: *value });
+        }
+
+        for (metric, value) in CLASS_METRICS.iter() {
+            registry.register(RuleLevel::Class, metric, op.clone(), ClassMetricEvaluator { value: *value });
+            registry.register(RuleLevel::Struct, metric, op.clone(), ClassMetricEvaluator { value: *value });
+        }
+
+        for (metric, value) in FILE_METRICS.iter() {
+            registry.register(RuleLevel::File, metric, op.clone(), FileMetricEvaluator { value: *value });
+        }
+    }
+
+    for op in STRING_OPS.iter() {
+        registry.register_shared(RuleLevel::Class, "name", op.clone(), class_name.clone());
+        registry.register_shared(RuleLevel::Struct, "name", op.clone(), class_name.clone());
+    }
+
+    let naming: Arc<dyn RuleEvaluator> = Arc::new(NamingEvaluator);
+    for (level, property) in NAMING_PROPERTIES.iter() {
+        registry.register_shared(*level, property, Operator::Be, naming.clone());
+    }
+
+    for level in [RuleLevel::Class, RuleLevel::Struct].iter() {
+        registry.register(*level, "", Operator::ResideIn, ResideInEvaluator);
+        registry.register(*level, "", Operator::Inside, ResideInEvaluator);
+        registry.register(*level, "", Operator::Accessed, AccessedEvaluator);
+    }
+}
+
+/// `package("..home..")::file.len should < 20;`
+pub struct FileCountEvaluator;
+
+impl RuleEvaluator for FileCountEvaluator {
+    fn evaluate(&self, ctx: &mut RuleContext, _models: &[CodeFile]) {
+        let actual = Number::from(ctx.filtered_models.len());
+        ctx.asserted = 1;
+        process_len(ctx, actual);
+    }
+}
+
+/// `class(".")::len should < 25;`
+pub struct ClassCountEvaluator;
+
+impl RuleEvaluator for ClassCountEvaluator {
+    fn evaluate(&self, ctx: &mut RuleContext, _models: &[CodeFile]) {
+        let actual = Number::from(ctx.filtered_classes.len());
+        ctx.asserted = 1;
+        process_len(ctx, actual);
+    }
+}
+
+/// `class(implementation "BaseParser")::name should endsWith "Parser";`
+pub struct ClassNameEvaluator;
+
+impl RuleEvaluator for ClassNameEvaluator {
+    fn evaluate(&self, ctx: &mut RuleContext, _models: &[CodeFile]) {
+        let excepted = GuardRule::assert_string(ctx.rule);
+        let has_not = ctx.has_not();
+
+        let mut error = RuleErrorMsg::new(MismatchType::FileName, ctx.index);
+
+        let match_func: fn(&str, &str) -> bool;
+        fn starts_with(input: &str, condition: &str) -> bool {
+            input.starts_with(condition)
+        }
+        fn ends_with(input: &str, condition: &str) -> bool {
+            input.ends_with(condition)
+        }
+        fn contains(input: &str, condition: &str) -> bool {
+            input.contains(condition)
+        }
+
+        match ctx.operator() {
+            Some(Operator::StartsWith) => {
+                error.msg = format!("startsWith: {:?}", excepted);
+                match_func = starts_with;
+            }
+            Some(Operator::Endswith) => {
+                error.msg = format!("endsWith: {:?}", excepted);
+                match_func = ends_with;
+            }
+            Some(Operator::Contains) => {
+                error.msg = format!("contains: {:?}", excepted);
+                match_func = contains;
+            }
+            _ => { return; }
+        }
+
+        ctx.asserted = ctx.filtered_classes.len();
+        let mut assert_success = true;
+        ctx.filtered_classes.iter().for_each(|(file, clz)| {
+            let mut is_match = match_func(&clz.name, &excepted);
+            if has_not {
+                is_match = !is_match
+            }
+            if !is_match {
+                assert_success = false;
+                error.items.push(ViolationItem::class(file, clz))
+            }
+        });
+
+        if !assert_success {
+            ctx.errors.push(error);
+        }
+    }
+}
+
+/// `class(".")::name should be PascalCase;`, also for methods, fields, constants, functions
+/// and files. Constructors, which are named by their classes, are skipped
+pub struct NamingEvaluator;
+
+impl RuleEvaluator for NamingEvaluator {
+    fn evaluate(&self, ctx: &mut RuleContext, _models: &[CodeFile]) {
+        let style = match ctx.rule.assert {
+            RuleAssert::Naming(style) => style,
+            _ => { return; }
+        };
+
+        let property = ctx.property();
+        let mut names: Vec<(ViolationItem, &str)> = vec![];
+        match (ctx.rule.level, property.as_str()) {
+            (RuleLevel::Function, _) => {
+                for (file, class, func) in &ctx.filtered_functions {
+                    if !is_constructor(*class, func) {
+                        names.push((ViolationItem::function(file, *class, func), &func.name));
+                    }
+                }
+            }
+            (RuleLevel::File, _) => {
+                for file in &ctx.filtered_models {
+                    let stem = file.file_name.split('.').next().unwrap_or("");
+                    names.push((ViolationItem::file(file), stem));
+                }
+            }
+            (_, "name") => {
+                for (file, class) in &ctx.filtered_classes {
+                    names.push((ViolationItem::class(file, class), &class.name));
+                }
+            }
+            (_, "function.name") => {
+                for (file, class) in &ctx.filtered_classes {
+                    for func in class.functions.iter().filter(|func| !is_constructor(Some(class), func)) {
+                        names.push((ViolationItem::function(file, Some(class), func), &func.name));
+                    }
+                }
+            }
+            (_, "field.name") | (_, "constant.name") => {
+                let only_constant = property == "constant.name";
+                for (file, class) in &ctx.filtered_classes {
+                    for field in class.constant.iter().filter(|field| !only_constant || field.is_constant) {
+                        names.push((ViolationItem::field(file, class, field), &field.name));
+                    }
+                }
+            }
+            _ => { return; }
+        }
+
+        ctx.asserted = names.len();
+        let has_not = ctx.has_not();
+        let mut error = RuleErrorMsg::new(MismatchType::FileName, ctx.index);
+        error.expected = style.to_string();
+
+        let mut actual = vec![];
+        for (item, name) in names {
+            let mut is_match = style.is_match(name);
+            if has_not {
+                is_match = !is_match;
+            }
+
+            if !is_match {
+                actual.push(name.to_string());
+                error.items.push(item);
+            }
+        }
+
+        if !error.items.is_empty() {
+            let not = if has_not { "not " } else { "" };
+            error.actual = actual.join(", ");
+            error.msg = format!("{} should {}be {}, actual: {}", property, not, style, error.actual);
+            ctx.errors.push(error);
+        }
+    }
+}
+
+fn is_constructor(class: Option<&CodeClass>, func: &CodeFunction) -> bool {
+    matches!(class, Some(class) if class.name == func.name)
+}
+
+/// `class(implementation "BaseParser") resideIn package("....parser");`
+pub struct ResideInEvaluator;
+
+impl RuleEvaluator for ResideInEvaluator {
+    fn evaluate(&self, ctx: &mut RuleContext, _models: &[CodeFile]) {
+        let (has_capture, _level, identifier) = GuardRule::package_level(ctx.rule);
+        if !has_capture {
+            let mut error = RuleErrorMsg::new(MismatchType::None, ctx.index);
+            error.msg = "resideIn needs a package identifier, like `package(\"..parser\")`".to_string();
+            ctx.errors.push(error);
+            return;
+        }
+
+        let matcher = match ctx.package_matcher(&identifier) {
+            Some(matcher) => matcher,
+            None => { return; }
+        };
+
+        let has_not = ctx.has_not();
+        let mut error = RuleErrorMsg::new(MismatchType::FileName, ctx.index);
+        error.msg = format!("resideIn: {:?}", identifier);
+
+        ctx.asserted = ctx.filtered_classes.len();
+        let mut assert_success = true;
+        ctx.filtered_classes.iter().for_each(|(file, clz)| {
+            let mut package_match = matcher.is_match(clz.package.as_str());
+            if has_not {
+                package_match = !package_match;
+            }
+
+            if !package_match {
+                error.items.push(ViolationItem::class(file, clz));
+                assert_success = false;
+            }
+        });
+
+        if !assert_success {
+            ctx.errors.push(error);
+        }
+    }
+}
+
+/// `class("..service..") only accessed(["..controller..", "..service.."]);`
+pub struct AccessedEvaluator;
+
+impl RuleEvaluator for AccessedEvaluator {
+    fn evaluate(&self, ctx: &mut RuleContext, models: &[CodeFile]) {
+        let identifiers = match &ctx.rule.assert {
+            RuleAssert::Stringed(pkg_identifier) => vec![pkg_identifier.clone()],
+            RuleAssert::ArrayStringed(identifiers) => identifiers.clone(),
+            _ => vec![]
+        };
+
+        let mut assert_models: Vec<&CodeFile> = vec![];
+        for ident in identifiers {
+            match ctx.package_matcher(&ident) {
+                Some(matcher) => assert_models.extend(filter_by_package_identifier(models, &matcher)),
+                None => { return; }
+            }
+        }
+
+        let mut pkg_identifier = "".to_string();
+        if let RuleScope::PathDefine(str) = &ctx.rule.scope {
+            pkg_identifier = str.clone();
+        }
+
+        let matcher = match ctx.package_matcher(&pkg_identifier) {
+            Some(matcher) => matcher,
+            None => { return; }
+        };
+
+        let checked: Vec<&CodeFile> = models.iter()
+            .filter(|file| !assert_models.iter().any(|assert| assert.path == file.path))
+            .collect();
+        ctx.asserted = checked.len();
+
+        let mut error = RuleErrorMsg::new(MismatchType::Access, ctx.index);
+        error.items = checked.iter()
+            .flat_map(|file| {
+                matched_imports(file, &matcher).into_iter()
+                    .map(move |import| ViolationItem::file(file).with_import(import))
+            })
+            .collect();
+
+        if !error.items.is_empty() {
+            ctx.errors.push(error);
+        }
+    }
+}
+
+/// `package("..domain..")::instability should < 0.3;`, every selected package is checked
+pub struct PackageMetricEvaluator;
+
+impl RuleEvaluator for PackageMetricEvaluator {
+    fn evaluate(&self, ctx: &mut RuleContext, models: &[CodeFile]) {
+        let property = ctx.property();
+        let packages: BTreeSet<&str> = ctx.filtered_models.iter()
+            .map(|file| file.package.as_str())
+            .collect();
+
+        let mut values = vec![];
+        for metrics in PackageMetrics::from_models(models) {
+            if !packages.contains(metrics.package.as_str()) {
+                continue;
+            }
+
+            match metrics.value(&property) {
+                Some(value) => values.push((ViolationItem::package(&metrics.package), Number::from(value))),
+                None => { return; }
+            }
+        }
+
+        process_elements(ctx, values);
+    }
+}
+
+/// `function("..")::complexity should <= 15;`, every selected function is checked
+pub struct FunctionMetricEvaluator {
+    pub value: fn(&CodeFunction) -> usize,
+}
+
+impl RuleEvaluator for FunctionMetricEvaluator {
+    fn evaluate(&self, ctx: &mut RuleContext, _models: &[CodeFile]) {
+        let values = ctx.filtered_functions.iter()
+            .map(|(file, class, func)| {
+                (ViolationItem::function(file, *class, func), Number::from((self.value)(func)))
+            })
+            .collect();
+
+        process_elements(ctx, values);
+    }
+}
+
+/// `class(".")::function.len should <= 20;`, every selected class is checked
+pub struct ClassMetricEvaluator {
+    pub value: fn(&CodeClass) -> usize,
+}
+
+impl RuleEvaluator for ClassMetricEvaluator {
+    fn evaluate(&self, ctx: &mut RuleContext, _models: &[CodeFile]) {
+        let values = ctx.filtered_classes.iter()
+            .map(|(file, class)| (ViolationItem::class(file, class), Number::from((self.value)(class))))
+            .collect();
+
+        process_elements(ctx, values);
+    }
+}
+
+/// `file("..")::loc should <= 500;`, every selected file is checked
+pub struct FileMetricEvaluator {
+    pub value: fn(&CodeFile) -> usize,
+}
+
+impl RuleEvaluator for FileMetricEvaluator {
+    fn evaluate(&self, ctx: &mut RuleContext, _models: &[CodeFile]) {
+        let values = ctx.filtered_models.iter()
+            .map(|file| (ViolationItem::file(file), Number::from((self.value)(file))))
+            .collect();
+
+        process_elements(ctx, values);
+    }
+}
+
+/// compare the value of every element with the number assert of rule, elements which fail
+/// are reported in one error
+pub fn process_elements(ctx: &mut RuleContext, values: Vec<(ViolationItem, Number)>) {
+    let operator = match ctx.operator() {
+        Some(op) => op.clone(),
+        None => { return; }
+    };
+
+    let property = ctx.property();
+    let expected = GuardRule::assert_number(ctx.rule);
+    let has_not = ctx.has_not();
+    ctx.asserted = values.len();
+
+    let mut error = RuleErrorMsg::new(MismatchType::Metric, ctx.index);
+    error.expected = expected.to_string();
+    error.msg = format!("{} should {} {}", property, operator_symbol(&operator), expected);
+
+    let mut actual = vec![];
+    for (item, value) in values {
+        let mut success = match compare_number(&operator, &value, &expected) {
+            Some(success) => success,
+            None => { return; }
+        };
+        if has_not {
+            success = !success;
+        }
+
+        if !success {
+            actual.push(format!("{} = {}", item.name, value));
+            error.items.push(item);
+        }
+    }
+
+    if !error.items.is_empty() {
+        error.actual = actual.join(", ");
+        ctx.errors.push(error);
+    }
+}
+
+/// imports (as written in source) which match `matcher`, resolved dependencies are matched
+/// by name or package, a wildcard import also matches the elements of its package. raw imports
+/// are used when models aren't resolved
+pub fn matched_imports<'a>(file: &'a CodeFile, matcher: &PackageMatcher) -> Vec<&'a str> {
+    if file.dependencies.is_empty() {
+        return file.imports.iter()
+            .filter(|imp| matcher.is_match(imp.as_str()))
+            .map(|imp| imp.as_str())
+            .collect();
+    }
+
+    file.dependencies.iter()
+        .filter(|dep| matcher.is_match(dep.name.as_str()) || matcher.is_match(dep.package.as_str())
+            || (dep.is_wildcard && matcher.is_match_in_package(dep.package.as_str())))
+        .map(|dep| dep.source.as_str())
+        .collect()
+}
+
+pub fn filter_by_package_identifier<'a>(models: &'a [CodeFile], matcher: &PackageMatcher) -> Vec<&'a CodeFile> {
+    models.iter()
+        .filter(|file| matcher.is_match(file.package.as_str()))
+        .collect()
+}
+
+/// compare `actual` with the number assert of rule, like `file.len should < 20`
+pub fn process_len(ctx: &mut RuleContext, actual_size: Number) {
+    let excepted_size = GuardRule::assert_number(ctx.rule);
+
+    let mut error = RuleErrorMsg::new(MismatchType::FileSize, ctx.index);
+    error.expected = excepted_size.to_string();
+    error.actual = actual_size.to_string();
+
+    let operator = match ctx.operator() {
+        Some(op) => op.clone(),
+        None => { return; }
+    };
+
+    let mut is_assert_fail = match compare_number(&operator, &actual_size, &excepted_size) {
+        Some(success) => !success,
+        None => { return; }
+    };
+    if ctx.has_not() {
+        is_assert_fail = !is_assert_fail;
+    }
+
+    if is_assert_fail {
+        let symbol = match operator {
+            Operator::Lte => "<= ".to_string(),
+            _ => operator_symbol(&operator).to_string()
+        };
+        error.msg = format!("file.len = {}, expected: len {} {}", actual_size, symbol, excepted_size);
+        ctx.errors.push(error);
+    }
+}
+
+/// `None` if `operator` isn't a comparison
+pub fn compare_number(operator: &Operator, actual: &Number, expected: &Number) -> Option<bool> {
+    let ordering = actual.partial_cmp(expected);
+    let success = match operator {
+        Operator::Gt => ordering == Some(Ordering::Greater),
+        Operator::Gte => ordering == Some(Ordering::Greater) || ordering == Some(Ordering::Equal),
+        Operator::Lt => ordering == Some(Ordering::Less),
+        Operator::Lte => ordering == Some(Ordering::Less) || ordering == Some(Ordering::Equal),
+        Operator::Eq => ordering == Some(Ordering::Equal),
+        Operator::Ineq => ordering != Some(Ordering::Equal),
+        _ => { return None; }
+    };
+
+    Some(success)
+}
+
+pub fn operator_symbol(operator: &Operator) -> &'static str {
+    match operator {
+        Operator::Gt => ">",
+        Operator::Gte => ">=",
+        Operator::Lt => "<",
+        Operator::Lte => "<=",
+        Operator::Eq => "=",
+        Operator::Ineq => "!=",
+        _ => ""
+    }
+}
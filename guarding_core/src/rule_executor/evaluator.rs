@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use guarding_parser::ast::{Operator, RuleLevel};
+
+use crate::domain::code_file::CodeFile;
+use crate::rule_executor::builtin;
+use crate::rule_executor::rule_context::RuleContext;
+
+/// Checks the elements selected by a rule, and pushes a `RuleErrorMsg` into the context when
+/// the assert fails.
+///
+/// Evaluators are looked up by rule level, property and operator, so a new check can be
+/// registered without touching the executor:
+///
+/// ```
+/// use guarding_core::domain::code_file::CodeFile;
+/// use guarding_core::rule_executor::{RuleContext, RuleEvaluator, RuleExecutor};
+/// use guarding_parser::ast::{Operator, RuleLevel};
+///
+/// struct NoTodoEvaluator;
+///
+/// impl RuleEvaluator for NoTodoEvaluator {
+///     fn evaluate(&self, _ctx: &mut RuleContext, _models: &[CodeFile]) {}
+/// }
+///
+/// let mut executor = RuleExecutor::default();
+/// executor.register(RuleLevel::Class, "comment", Operator::Contains, NoTodoEvaluator);
+/// ```
+pub trait RuleEvaluator: Send + Sync {
+    /// `models` are all files, the rule's own selection lives in `ctx`
+    fn evaluate(&self, ctx: &mut RuleContext, models: &[CodeFile]);
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct EvaluatorKey {
+    pub level: RuleLevel,
+    /// property chain joined by dot, like `file.len`, empty for rules without expression
+    pub property: String,
+    /// operator without `not`
+    pub operator: Operator,
+}
+
+impl EvaluatorKey {
+    pub fn new(level: RuleLevel, property: &str, operator: Operator) -> EvaluatorKey {
+        EvaluatorKey {
+            level,
+            property: property.to_string(),
+            operator,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct EvaluatorRegistry {
+    evaluators: HashMap<EvaluatorKey, Arc<dyn RuleEvaluator>>,
+}
+
+impl EvaluatorRegistry {
+    /// registry without any evaluator, use `Default` for the built-in ones
+    pub fn empty() -> EvaluatorRegistry {
+        EvaluatorRegistry {
+            evaluators: Default::default()
+        }
+    }
+
+    /// register an evaluator, an existing one with same key will be replaced
+    pub fn register<E: RuleEvaluator + 'static>(&mut self, level: RuleLevel, property: &str, operator: Operator, evaluator: E) {
+        self.register_shared(level, property, operator, Arc::new(evaluator));
+    }
+
+    /// register one evaluator for multiple keys
+    pub fn register_shared(&mut self, level: RuleLevel, property: &str, operator: Operator, evaluator: Arc<dyn RuleEvaluator>) {
+        self.evaluators.insert(EvaluatorKey::new(level, property, operator), evaluator);
+    }
+
+    pub fn get(&self, key: &EvaluatorKey) -> Option<&Arc<dyn RuleEvaluator>> {
+        self.evaluators.get(key)
+    }
+
+    pub fn contains(&self, key: &EvaluatorKey) -> bool {
+        self.evaluators.contains_key(key)
+    }
+}
+
+impl Default for EvaluatorRegistry {
+    fn default() -> Self {
+        let mut registry = EvaluatorRegistry::empty();
+        builtin::register_builtin(&mut registry);
+        registry
+    }
+}
+
+impl fmt::Debug for EvaluatorRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.evaluators.keys()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use guarding_parser::ast::{Operator, RuleLevel};
+
+    use crate::domain::code_file::CodeFile;
+    use crate::rule_executor::evaluator::{EvaluatorKey, EvaluatorRegistry, RuleEvaluator};
+    use crate::rule_executor::rule_context::RuleContext;
+
+    struct EmptyEvaluator;
+
+    impl RuleEvaluator for EmptyEvaluator {
+        fn evaluate(&self, _ctx: &mut RuleContext, _models: &[CodeFile]) {}
+    }
+
+    #[test]
+    fn should_register_builtin_evaluators() {
+        let registry = EvaluatorRegistry::default();
+
+        assert!(registry.contains(&EvaluatorKey::new(RuleLevel::Package, "file.len", Operator::Lt)));
+        assert!(registry.contains(&EvaluatorKey::new(RuleLevel::Class, "name", Operator::Endswith)));
+        assert!(registry.contains(&EvaluatorKey::new(RuleLevel::Class, "", Operator::Accessed)));
+        assert!(!registry.contains(&EvaluatorKey::new(RuleLevel::Function, "name", Operator::Endswith)));
+    }
+
+    #[test]
+    fn should_register_custom_evaluator() {
+        let mut registry = EvaluatorRegistry::empty();
+        registry.register(RuleLevel::Function, "name", Operator::Contains, EmptyEvaluator);
+
+        assert!(registry.contains(&EvaluatorKey::new(RuleLevel::Function, "name", Operator::Contains)));
+    }
+}
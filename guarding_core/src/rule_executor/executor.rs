@@ -1,27 +1,41 @@
-use guarding_parser::ast::{Expr, GuardRule, Operator, RuleAssert, RuleLevel, RuleScope};
+use std::time::Instant;
+
+use rayon::prelude::*;
+
+use guarding_parser::ast::{GuardRule, Operator, RuleLevel, RuleScope};
 
 use crate::domain::code_class::CodeClass;
 use crate::domain::code_file::CodeFile;
-use crate::rule_executor::package_matcher::is_package_match;
+use crate::rule_executor::builtin::filter_by_package_identifier;
+use crate::rule_executor::evaluator::{EvaluatorKey, EvaluatorRegistry, RuleEvaluator};
+use crate::rule_executor::rule_context::RuleContext;
 use crate::rule_executor::rule_error::{MismatchType, RuleErrorMsg};
+use crate::rule_executor::rule_report::{RuleReport, RuleStatus};
 
 #[derive(Debug, Clone)]
 pub struct RuleExecutor {
     pub errors: Vec<RuleErrorMsg>,
+    /// one report for every rule, ordered by rule index
+    pub reports: Vec<RuleReport>,
     pub rules: Vec<GuardRule>,
     pub models: Vec<CodeFile>,
-    pub filtered_models: Vec<CodeFile>,
-    pub filtered_classes: Vec<CodeClass>,
+    pub registry: EvaluatorRegistry,
+    /// threads for evaluating rules, `1` runs sequentially, `0` uses all cores
+    pub threads: usize,
+    /// fail rules which select nothing, like a typo in `..controler..`, overridden by `failOnEmpty` or `allowEmpty` of a rule
+    pub fail_on_empty: bool,
 }
 
 impl Default for RuleExecutor {
     fn default() -> Self {
         RuleExecutor {
             errors: Default::default(),
+            reports: vec![],
             rules: vec![],
             models: vec![],
-            filtered_models: vec![],
-            filtered_classes: vec![],
+            registry: Default::default(),
+            threads: 1,
+            fail_on_empty: false,
         }
     }
 }
@@ -30,417 +44,190 @@ impl RuleExecutor {
     pub fn new(models: Vec<CodeFile>, rules: Vec<GuardRule>) -> RuleExecutor {
         RuleExecutor {
             errors: vec![],
+            reports: vec![],
             rules,
             models,
-            filtered_models: vec![],
-            filtered_classes: vec![],
+            registry: Default::default(),
+            threads: 1,
+            fail_on_empty: false,
         }
     }
 
-    pub fn run(&mut self) {
-        self.rules
-            .clone()
-            .into_iter()
-            .enumerate()
-            .for_each(|(i, rule)| {
-                self.capture(rule, i);
-            });
+    /// add or replace the evaluator for `level::property operator`
+    pub fn register<E: RuleEvaluator + 'static>(&mut self, level: RuleLevel, property: &str, operator: Operator, evaluator: E) {
+        self.registry.register(level, property, operator, evaluator);
     }
 
-    pub fn capture(&mut self, rule: GuardRule, index: usize) {
-        match &rule.level {
-            RuleLevel::Package => {
-                self.capture_package(&rule, index)
-            }
-            RuleLevel::Function => {
-                println!("todo");
-            }
-            RuleLevel::Class => {
-                self.capture_class(&rule, index)
-            }
-            RuleLevel::Struct => {
-                println!("todo");
-            }
-        };
-
-        // todo: 3. run assert
+    pub fn with_threads(mut self, threads: usize) -> RuleExecutor {
+        self.threads = threads;
+        self
     }
 
-    fn capture_class(&mut self, rule: &GuardRule, index: usize) {
-        // - accessed(["..controller..", "..service.."]);
-        // - dependBy ""
-        if self.capture_package_to_package(&rule, index) {
-            return;
-        }
-
-        // filter package to class assert
-        // filter class to class assert
-        self.filter_classes_by_scope(&rule);
-
-        self.execute_classes_assert(&rule, index)
+    pub fn with_fail_on_empty(mut self, fail_on_empty: bool) -> RuleExecutor {
+        self.fail_on_empty = fail_on_empty;
+        self
     }
 
-    fn filter_classes_by_scope(&mut self, rule: &&GuardRule) {
-        match &rule.scope {
-            RuleScope::PathDefine(str) => {
-                if str.as_str() == "." {
-                    for file in &self.models {
-                        self.filtered_classes.extend(file.classes.clone());
-                    }
-                } else {
-                    for file in &self.filter_classes_by_package_identifier(str) {
-                        self.filtered_classes.extend(file.classes.clone());
-                    }
-                }
-            }
-            RuleScope::Implementation(str) => {
-                let mut filtered_classes = vec![];
-                let _ = &self.models.iter().for_each(|file| {
-                    let classes: Vec<CodeClass> = file.classes.iter()
-                        .filter(|class| {
-                            class.implements.contains(str)
-                        })
-                        .map(|s| s.clone())
-                        .collect();
-
-                    filtered_classes.extend(classes);
-                });
+    /// errors and reports are always ordered by rule index, no matter how many threads are used
+    pub fn run(&mut self) {
+        let results = if self.threads == 1 {
+            self.rules.iter()
+                .enumerate()
+                .map(|(i, rule)| self.evaluate_rule(rule, i))
+                .collect::<Vec<(Vec<RuleErrorMsg>, RuleReport)>>()
+        } else {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(self.threads)
+                .build()
+                .expect("create thread pool error");
+
+            pool.install(|| {
+                self.rules.par_iter()
+                    .enumerate()
+                    .map(|(i, rule)| self.evaluate_rule(rule, i))
+                    .collect::<Vec<(Vec<RuleErrorMsg>, RuleReport)>>()
+            })
+        };
 
-                self.filtered_classes.extend(filtered_classes);
-            }
-            _ => {}
+        for (errors, report) in results {
+            self.errors.extend(errors);
+            self.reports.push(report);
         }
     }
 
-    fn execute_classes_assert(&mut self, rule: &&GuardRule, index: usize) {
-        match &rule.expr {
-            Expr::PropsCall(props) => {
-                match props[0].as_str() {
-                    "len" => {
-                        let size = GuardRule::assert_sized(&rule);
-                        self.process_len(index, size, &rule.ops, self.filtered_classes.len())
-                    }
-                    "name" => {
-                        let string = GuardRule::assert_string(&rule);
-                        self.process_name(index, &rule.ops, string)
-                    }
-                    _ => {
-                        println!("todo: expr {:?}", props[0].as_str());
-                    }
-                }
-            }
-            Expr::Identifier(ident) => {
-                match ident.as_str() {
-                    "" => {
-                        let (has_capture, _level, ident) = GuardRule::package_level(&rule);
-                        if has_capture {
-                            self.process_package_captures(index, &rule.ops, ident)
-                        } else {
-                            println!("Empty Identifier: {:?}", ident);
-                        }
-                    }
-                    &_ => {
-                        println!("Expr::Identifier: {:?}", ident);
-                    }
-                }
-            }
-        }
+    pub fn capture(&mut self, rule: GuardRule, index: usize) {
+        let (errors, report) = self.evaluate_rule(&rule, index);
+        self.errors.extend(errors);
+        self.reports.push(report);
     }
 
-    fn capture_package_to_package(&mut self, rule: &&GuardRule, index: usize) -> bool {
-        let mut has_capture_assert = false;
+    fn evaluate_rule(&self, rule: &GuardRule, index: usize) -> (Vec<RuleErrorMsg>, RuleReport) {
+        let start = Instant::now();
+        let mut report = RuleReport::new(rule, index);
+
+        let mut ctx = RuleContext::new(rule, index);
+        let supported = self.evaluate(&mut ctx);
+
+        report.selected = ctx.selected();
+        if supported && ctx.errors.is_empty() && report.selected == 0 && self.is_fail_on_empty(rule) {
+            let mut error = RuleErrorMsg::new(MismatchType::Empty, index);
+            error.expected = "at least one element".to_string();
+            error.actual = "0".to_string();
+            error.msg = format!("`{}` selects nothing, please check the scope of the rule", report.id);
+            ctx.errors.push(error);
+        }
+
+        report.asserted = ctx.asserted;
+        report.errors = ctx.errors.len();
+        report.status = if !supported {
+            RuleStatus::Unsupported
+        } else if !ctx.errors.is_empty() {
+            RuleStatus::Failed
+        } else if report.selected == 0 {
+            RuleStatus::Empty
+        } else {
+            RuleStatus::Passed
+        };
+        report.elapsed_ms = start.elapsed().as_secs_f64() * 1000.0;
 
-        let mut assert_models: Vec<CodeFile> = vec![];
+        (ctx.errors, report)
+    }
 
-        let operator = &rule.ops[0];
-        match operator {
-            Operator::Accessed => {
-                match &rule.assert {
-                    RuleAssert::Stringed(pkg_identifier) => {
-                        assert_models = self.filter_classes_by_package_identifier(pkg_identifier);
-                    }
-                    RuleAssert::ArrayStringed(identifiers) => {
-                        for ident in identifiers {
-                            assert_models.extend(self.filter_classes_by_package_identifier(ident));
-                        }
-                    }
-                    _ => {}
-                }
+    fn is_fail_on_empty(&self, rule: &GuardRule) -> bool {
+        rule.fail_on_empty.unwrap_or(self.fail_on_empty)
+    }
 
-                has_capture_assert = true;
-            }
-            Operator::DependBy => {
-                has_capture_assert = true;
-            }
-            _ => {}
-        }
+    /// evaluate a rule inside its own context, nothing is shared with other rules.
+    /// returns `false` if there is no evaluator for the rule
+    pub fn evaluate<'a>(&'a self, ctx: &mut RuleContext<'a>) -> bool {
+        // 1. filter models from `rule_level` with `rule_scope`
+        self.select(ctx);
 
-        self.filtered_models = assert_models;
+        // 2. run expression & assert by evaluator
+        let operator = match ctx.operator() {
+            Some(op) => op.clone(),
+            None => { return false; }
+        };
 
-        let mut pkg_identifier = "".to_string();
-        match &rule.scope {
-            RuleScope::PathDefine(str) => {
-                pkg_identifier = str.clone();
+        let key = EvaluatorKey::new(ctx.rule.level, ctx.property().as_str(), operator);
+        match self.registry.get(&key) {
+            Some(evaluator) => {
+                evaluator.evaluate(ctx, &self.models);
+                true
             }
-            _ => {}
+            None => false
         }
+    }
 
-        let mut error = RuleErrorMsg::new(MismatchType::Access, index);
-        let mut assert_success = true;
-
-        match operator {
-            Operator::Accessed => {
-                let paths = self.search_by_access(pkg_identifier);
-                if paths.len() > 0 {
-                    assert_success = false;
-                    paths.iter().for_each(|p| {
-                        error.items.push(p.clone());
-                    });
-                }
+    fn select<'a>(&'a self, ctx: &mut RuleContext<'a>) {
+        match ctx.rule.level {
+            RuleLevel::Package | RuleLevel::File => {
+                ctx.filtered_models = self.filter_models_by_scope(ctx);
+            }
+            RuleLevel::Class | RuleLevel::Struct => {
+                ctx.filtered_classes = self.filter_classes_by_scope(ctx);
+            }
+            RuleLevel::Function => {
+                ctx.filtered_functions = self.filter_models_by_scope(ctx).into_iter()
+                    .flat_map(|file| {
+                        let functions = file.functions.iter().map(move |func| (file, None, func));
+                        let methods = file.classes.iter().flat_map(move |class| {
+                            class.functions.iter().map(move |func| (file, Some(class), func))
+                        });
+                        functions.chain(methods)
+                    })
+                    .collect();
             }
-            _ => {}
-        }
-
-        if !assert_success {
-            self.errors.push(error);
         }
-
-        has_capture_assert
     }
 
-    fn search_by_access(&mut self, pkg_identifier: String) -> Vec<String> {
-        let mut error_paths = vec![];
-        self.models.iter().for_each(|clz| {
-            for imp in &clz.imports {
-                let is_file_import = is_package_match(pkg_identifier.clone(), imp.as_str());
-                if is_file_import {
-                    let mut has_file_in_assert = false;
-                    let _ = &self.filtered_models.iter().for_each(|file| {
-                        if file.path == clz.path {
-                            has_file_in_assert = true;
-                        }
-                    });
+    fn filter_models_by_scope<'a>(&'a self, ctx: &mut RuleContext<'a>) -> Vec<&'a CodeFile> {
+        match &ctx.rule.scope {
+            RuleScope::PathDefine(str) => {
+                if str.as_str() == "." {
+                    return self.models.iter().collect();
+                }
 
-                    if !has_file_in_assert {
-                        error_paths.push(clz.path.clone());
-                    }
+                match ctx.package_matcher(str) {
+                    Some(matcher) => filter_by_package_identifier(&self.models, &matcher),
+                    None => vec![]
                 }
             }
-        });
-
-        error_paths
+            _ => vec![]
+        }
     }
 
-    fn capture_package(&mut self, rule: &GuardRule, index: usize) {
-        match &rule.scope {
+    fn filter_classes_by_scope<'a>(&'a self, ctx: &mut RuleContext<'a>) -> Vec<(&'a CodeFile, &'a CodeClass)> {
+        let mut filtered_classes = vec![];
+        match &ctx.rule.scope {
             RuleScope::PathDefine(str) => {
-                let path = str.as_str();
-                if path == "." {
-                    self.filtered_models = self.models.clone();
+                let files = if str.as_str() == "." {
+                    self.models.iter().collect()
+                } else if let Some(matcher) = ctx.package_matcher(str) {
+                    filter_by_package_identifier(&self.models, &matcher)
                 } else {
-                    self.filtered_models = self.filter_classes_by_package_identifier(str);
+                    vec![]
                 };
-            }
-            RuleScope::MatchRegex(_) => {}
-            _ => {}
-        }
 
-        // 2. run expression for evaluation
-        match &rule.expr {
-            Expr::PropsCall(props) => {
-                match props[0].as_str() {
-                    "len" => {
-                        let size = GuardRule::assert_sized(&rule);
-                        self.process_len(index, size, &rule.ops, self.filtered_models.len())
-                    }
-                    "file" => {
-                        match props[1].as_str() {
-                            "len" => {
-                                let size = GuardRule::assert_sized(&rule);
-                                self.process_len(index, size, &rule.ops, self.filtered_models.len())
-                            }
-                            &_ => {}
-                        };
-                    }
-                    &_ => {}
+                for file in files {
+                    let classes = file.classes.iter()
+                        .filter(|class| !class.is_interface())
+                        .map(|class| (file, class));
+                    filtered_classes.extend(classes);
                 }
             }
-            Expr::Identifier(_) => {}
-        }
-    }
-
-    fn filter_classes_by_package_identifier(&mut self, str: &String) -> Vec<CodeFile> {
-        self.models.iter()
-            .filter(|s| { is_package_match(str.to_string(), s.package.as_str()) })
-            .map(|s| { s.clone() })
-            .collect()
-    }
-
-    fn process_package_captures(&mut self, index: usize, all_ops: &Vec<Operator>, identifier: String) {
-        let mut ops = &all_ops[0];
-        let mut has_not = false;
-        match ops {
-            Operator::Not => {
-                ops = &all_ops[1];
-                has_not = true;
-            }
-            _ => {}
-        }
-
-        let mut error = RuleErrorMsg::new(MismatchType::FileName, index);
-
-        let mut assert_success = true;
-        match ops {
-            Operator::Inside |
-            Operator::ResideIn => {
-                error.msg = format!("resideIn: {:?}", identifier);
-                self.filtered_classes.iter().for_each(|clz| {
-                    let mut package_match = is_package_match(identifier.clone(), clz.package.as_str());
-                    if has_not {
-                        package_match = !package_match;
-                    }
+            RuleScope::Implementation(str) => {
+                self.models.iter().for_each(|file| {
+                    let classes = file.classes.iter()
+                        .filter(|class| class.implements.contains(str))
+                        .map(|class| (file, class));
 
-                    if !package_match {
-                        let item = format!("path: {}, name: {}", clz.package.clone(), clz.name.clone());
-                        error.items.push(item);
-                        assert_success = false;
-                    }
+                    filtered_classes.extend(classes);
                 });
             }
             _ => {}
         }
 
-        if !assert_success {
-            self.errors.insert(index, error);
-        }
-    }
-
-    fn process_name(&mut self, index: usize, all_ops: &Vec<Operator>, excepted: String) {
-        let mut ops = &all_ops[0];
-        let mut has_not = false;
-        match ops {
-            Operator::Not => {
-                ops = &all_ops[1];
-                has_not = true;
-            }
-            _ => {}
-        }
-
-        let mut error = RuleErrorMsg::new(MismatchType::FileName, index);
-
-        let match_func: fn(String, &String) -> bool;
-        fn starts_with(input: String, condition: &String) -> bool {
-            return input.starts_with(condition);
-        }
-        fn ends_with(input: String, condition: &String) -> bool {
-            return input.ends_with(condition);
-        }
-        fn contains(input: String, condition: &String) -> bool {
-            return input.contains(condition);
-        }
-
-        let mut assert_success = true;
-        match ops {
-            Operator::StartsWith => {
-                error.msg = format!("startsWith: {:?}", excepted);
-                match_func = starts_with;
-            }
-            Operator::Endswith => {
-                error.msg = format!("endsWith: {:?}", excepted);
-                match_func = ends_with;
-            }
-            Operator::Contains => {
-                error.msg = format!("contains: {:?}", excepted);
-                match_func = contains;
-            }
-            _ => { return; }
-        }
-
-        self.filtered_classes.iter().for_each(|clz| {
-            let mut is_starts_with = match_func(clz.name.clone(), &excepted);
-            if has_not {
-                is_starts_with = !is_starts_with
-            }
-            if !is_starts_with {
-                assert_success = false;
-                let item = format!("path: {}, name: {}", clz.package.clone(), clz.name.clone());
-                error.items.push(item)
-            }
-        });
-
-        if !assert_success {
-            self.errors.insert(index, error);
-        }
-    }
-
-    fn process_len(&mut self, index: usize, excepted_size: usize, all_ops: &Vec<Operator>, actual_size: usize) {
-        let mut ops = &all_ops[0];
-        let mut has_not = false;
-        match ops {
-            Operator::Not => {
-                ops = &all_ops[1];
-                has_not = true;
-            }
-            _ => {}
-        }
-
-        let mut error = RuleErrorMsg::new(MismatchType::FileSize, index);
-        error.expected = excepted_size.to_string();
-        error.actual = actual_size.to_string();
-
-        let is_assert_success: fn(usize, usize) -> bool;
-        fn gt(actual: usize, excepted: usize) -> bool {
-            return actual > excepted;
-        }
-        fn gte(actual: usize, excepted: usize) -> bool {
-            return actual >= excepted;
-        }
-        fn lt(actual: usize, excepted: usize) -> bool {
-            return actual < excepted;
-        }
-        fn lte(actual: usize, excepted: usize) -> bool {
-            return actual <= excepted;
-        }
-        fn eq(actual: usize, excepted: usize) -> bool {
-            return actual == excepted;
-        }
-        fn ineq(actual: usize, excepted: usize) -> bool { return actual != excepted; }
-
-        match ops {
-            Operator::Gt => {
-                error.msg = format!("file.len = {}, expected: len > {}", actual_size, excepted_size);
-                is_assert_success = gt;
-            }
-            Operator::Gte => {
-                error.msg = format!("file.len = {}, expected: len >= {}", actual_size, excepted_size);
-                is_assert_success = gte;
-            }
-            Operator::Lt => {
-                error.msg = format!("file.len = {}, expected: len < {}", actual_size, excepted_size);
-                is_assert_success = lt;
-            }
-            Operator::Lte => {
-                error.msg = format!("file.len = {}, expected: len <=  {}", actual_size, excepted_size);
-                is_assert_success = lte;
-            }
-            Operator::Eq => {
-                error.msg = format!("file.len = {}, expected: len = {}", actual_size, excepted_size);
-                is_assert_success = eq;
-            }
-            Operator::Ineq => {
-                error.msg = format!("file.len = {}, expected: len != {}", actual_size, excepted_size);
-                is_assert_success = ineq;
-            }
-            _ => { return; }
-        }
-
-        let mut is_assert_fail = !is_assert_success(actual_size, excepted_size);
-        if has_not {
-            is_assert_fail = !is_assert_fail;
-        }
-
-        if is_assert_fail {
-            self.errors.push(error);
-        }
+        filtered_classes
     }
-}
\ No newline at end of file
+}
@@ -1,6 +1,15 @@
 pub use executor::RuleExecutor;
-pub use rule_error::RuleErrorMsg;
+pub use rule_error::{ElementKind, RuleErrorMsg, ViolationItem};
+pub use rule_context::RuleContext;
+pub use baseline::Baseline;
+pub use rule_report::{RuleReport, RuleStatus};
+pub use evaluator::{EvaluatorKey, EvaluatorRegistry, RuleEvaluator};
 
 pub mod executor;
 pub mod package_matcher;
 pub mod rule_error;
+pub mod rule_context;
+pub mod evaluator;
+pub mod builtin;
+pub mod baseline;
+pub mod rule_report;
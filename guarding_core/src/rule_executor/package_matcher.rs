@@ -15,30 +15,73 @@
  */
 use regex::Regex;
 
-pub fn is_package_match(package_identifier: String, text: &str) -> bool {
-    let package = convert_to_regex(package_identifier);
-    let regex = Regex::new(package.as_str())
-        .expect("regex error");
-
-    regex.is_match(text)
+/// A compiled ArchUnit-style package identifier, like `..service..` or `com.(*)..`.
+///
+/// compile it once and reuse it for all files, `(*)` and `(**)` are exposed as capture groups.
+#[derive(Debug, Clone)]
+pub struct PackageMatcher {
+    identifier: String,
+    regex: Regex,
 }
 
-pub fn is_assert_match(package_identifier: String, text: &str, assert_package: String) -> bool {
-    let package = convert_to_regex(package_identifier);
-    let regex = Regex::new(package.as_str())
-        .expect("regex error");
-
-    if let Some(caps) = regex.captures(text) {
-        for ma in caps.iter() {
-            if let Some(match_) = ma {
-                if match_.as_str() == assert_package {
-                    return true
-                }
-            }
+impl PackageMatcher {
+    pub fn new(package_identifier: &str) -> Result<PackageMatcher, regex::Error> {
+        let regex = Regex::new(convert_to_regex(package_identifier.to_string()).as_str())?;
+        Ok(PackageMatcher {
+            identifier: package_identifier.to_string(),
+            regex,
+        })
+    }
+
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.regex.is_match(text)
+    }
+
+    /// is the element of identifier in `package`, so that a wildcard import of the package
+    /// accesses it: `java.util.Map` and `..util.Map` are in `java.util`
+    pub fn is_match_in_package(&self, package: &str) -> bool {
+        let name = self.identifier.rsplit('.').next().unwrap_or("");
+        let is_name = !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '$');
+        is_name && self.is_match(&format!("{}.{}", package, name))
+    }
+
+    /// values of `(*)` and `(**)` groups, `None` if text isn't matched
+    pub fn captures(&self, text: &str) -> Option<Vec<String>> {
+        self.regex.captures(text).map(|caps| {
+            caps.iter()
+                .skip(1)
+                .map(|group| group.map(|m| m.as_str().to_string()).unwrap_or_default())
+                .collect()
+        })
+    }
+
+    /// is the whole match or one of the `(*)` / `(**)` groups equal to `assert_package`
+    pub fn is_group_match(&self, text: &str, assert_package: &str) -> bool {
+        match self.regex.captures(text) {
+            Some(caps) => caps.iter().flatten().any(|group| group.as_str() == assert_package),
+            None => false
         }
     }
+}
 
-    return false;
+/// compile and match in one call, use [`PackageMatcher`] when matching many texts.
+/// an invalid identifier will never match.
+pub fn is_package_match(package_identifier: String, text: &str) -> bool {
+    match PackageMatcher::new(package_identifier.as_str()) {
+        Ok(matcher) => matcher.is_match(text),
+        Err(_) => false
+    }
+}
+
+pub fn is_assert_match(package_identifier: String, text: &str, assert_package: String) -> bool {
+    match PackageMatcher::new(package_identifier.as_str()) {
+        Ok(matcher) => matcher.is_group_match(text, assert_package.as_str()),
+        Err(_) => false
+    }
 }
 
 pub fn convert_to_regex(package_identifier: String) -> String {
@@ -54,7 +97,7 @@ pub fn convert_to_regex(package_identifier: String) -> String {
 
 #[cfg(test)]
 mod tests {
-    use crate::rule_executor::package_matcher::{is_package_match, is_assert_match};
+    use crate::rule_executor::package_matcher::{is_package_match, is_assert_match, PackageMatcher};
 
     #[test]
     fn should_match() {
@@ -106,6 +149,46 @@ mod tests {
                                            "arbitrary".to_string()
         );
 
-        assert_eq!(true, assert_match);
+        assert!(assert_match);
+    }
+
+    #[test]
+    fn should_match_whole_text_as_group() {
+        assert!(is_assert_match("some.(*).pkg".to_string(), "some.arbitrary.pkg", "some.arbitrary.pkg".to_string()));
+        assert!(!is_assert_match("some.(*).pkg".to_string(), "some.arbitrary.pkg", "pkg".to_string()));
+    }
+
+    #[test]
+    fn should_reuse_compiled_matcher() {
+        let matcher = PackageMatcher::new("..service..").unwrap();
+
+        assert!(matcher.is_match("com.phodal.service.impl"));
+        assert!(matcher.is_match("service"));
+        assert!(!matcher.is_match("com.phodal.controller"));
+        assert_eq!("..service..", matcher.identifier());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn should_match_element_in_package() {
+        assert!(PackageMatcher::new("java.util.Map").unwrap().is_match_in_package("java.util"));
+        assert!(PackageMatcher::new("..util.Map").unwrap().is_match_in_package("java.util"));
+        assert!(!PackageMatcher::new("java.util.Map").unwrap().is_match_in_package("java.awt"));
+        assert!(!PackageMatcher::new("..service..").unwrap().is_match_in_package("app"));
+    }
+
+    #[test]
+    fn should_expose_capture_groups() {
+        let matcher = PackageMatcher::new("com.(*).(**)").unwrap();
+
+        assert_eq!(Some(vec!["phodal".to_string(), "pepper.refactor".to_string()]),
+                   matcher.captures("com.phodal.pepper.refactor"));
+        assert_eq!(None, matcher.captures("org.phodal"));
+        assert!(matcher.is_group_match("com.phodal.pepper", "pepper"));
+    }
+
+    #[test]
+    fn should_return_error_for_invalid_identifier() {
+        assert!(PackageMatcher::new("com.(phodal").is_err());
+        assert!(!is_package_match("com.(phodal".to_string(), "com.phodal"));
+    }
+}
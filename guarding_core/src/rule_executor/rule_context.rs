@@ -0,0 +1,75 @@
+use guarding_parser::ast::{Expr, GuardRule, Operator};
+
+use crate::domain::code_class::CodeClass;
+use crate::domain::code_file::CodeFile;
+use crate::domain::code_function::CodeFunction;
+use crate::rule_executor::package_matcher::PackageMatcher;
+use crate::rule_executor::rule_error::{MismatchType, RuleErrorMsg};
+
+/// Evaluation state of a single rule.
+///
+/// A context is created for every rule and dropped after it, so the selection of one rule
+/// can never leak into the next one, and results don't depend on rule order.
+/// The selection only borrows from the models, which are shared read-only by all rules.
+#[derive(Debug, Clone)]
+pub struct RuleContext<'a> {
+    pub rule: &'a GuardRule,
+    pub index: usize,
+    pub filtered_models: Vec<&'a CodeFile>,
+    /// selected classes with the files they are declared in
+    pub filtered_classes: Vec<(&'a CodeFile, &'a CodeClass)>,
+    /// selected functions with their files, and classes for methods
+    pub filtered_functions: Vec<(&'a CodeFile, Option<&'a CodeClass>, &'a CodeFunction)>,
+    /// elements checked by the evaluator, set by evaluators
+    pub asserted: usize,
+    pub errors: Vec<RuleErrorMsg>,
+}
+
+impl<'a> RuleContext<'a> {
+    pub fn new(rule: &'a GuardRule, index: usize) -> RuleContext<'a> {
+        RuleContext {
+            rule,
+            index,
+            filtered_models: vec![],
+            filtered_classes: vec![],
+            filtered_functions: vec![],
+            asserted: 0,
+            errors: vec![],
+        }
+    }
+
+    /// count of selected files, classes and functions
+    pub fn selected(&self) -> usize {
+        self.filtered_models.len() + self.filtered_classes.len() + self.filtered_functions.len()
+    }
+
+    /// property chain of expression, `class::function.name` will be `function.name`
+    pub fn property(&self) -> String {
+        match &self.rule.expr {
+            Expr::PropsCall(props) => props.join("."),
+            Expr::Identifier(ident) => ident.clone(),
+        }
+    }
+
+    /// the assert operator, skip the `not` prefix
+    pub fn operator(&self) -> Option<&Operator> {
+        self.rule.ops.iter().find(|op| **op != Operator::Not)
+    }
+
+    pub fn has_not(&self) -> bool {
+        self.rule.ops.first() == Some(&Operator::Not)
+    }
+
+    /// compile a package identifier of the rule, an invalid identifier is reported as rule error
+    pub fn package_matcher(&mut self, identifier: &str) -> Option<PackageMatcher> {
+        match PackageMatcher::new(identifier) {
+            Ok(matcher) => Some(matcher),
+            Err(err) => {
+                let mut error = RuleErrorMsg::new(MismatchType::None, self.index);
+                error.msg = format!("invalid package identifier {:?}: {}", identifier, err);
+                self.errors.push(error);
+                None
+            }
+        }
+    }
+}
@@ -1,5 +1,10 @@
 use serde::{Deserialize, Serialize};
 
+use crate::domain::code_class::{ClassConstant, CodeClass};
+use crate::domain::code_file::CodeFile;
+use crate::domain::code_function::CodeFunction;
+use crate::domain::CodePoint;
+
 #[repr(C)]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum MismatchType {
@@ -7,6 +12,9 @@ pub enum MismatchType {
     Access,
     FileName,
     FileSize,
+    Metric,
+    /// nothing is selected by a rule which fails on empty
+    Empty,
 }
 
 #[repr(C)]
@@ -16,7 +24,7 @@ pub struct RuleErrorMsg {
     pub actual: String,
     pub mismatch_type: MismatchType,
     pub msg: String,
-    pub items: Vec<String>,
+    pub items: Vec<ViolationItem>,
     pub rule_index: usize,
 }
 
@@ -45,3 +53,148 @@ impl Default for RuleErrorMsg {
         }
     }
 }
+
+#[repr(C)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ElementKind {
+    Package,
+    File,
+    Class,
+    Function,
+    Field,
+}
+
+/// An element which breaks the rule.
+#[repr(C)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ViolationItem {
+    pub kind: ElementKind,
+    /// fully qualified name, like `com.phodal.pepper.parser.JsonParser`, package for a file
+    pub name: String,
+    /// path of the file which contains the element
+    pub path: String,
+    pub start: CodePoint,
+    pub end: CodePoint,
+    /// the offending import, only for access rules
+    pub import: Option<String>,
+}
+
+impl ViolationItem {
+    pub fn package(package: &str) -> ViolationItem {
+        ViolationItem {
+            kind: ElementKind::Package,
+            name: package.to_string(),
+            path: "".to_string(),
+            start: Default::default(),
+            end: Default::default(),
+            import: None,
+        }
+    }
+
+    pub fn file(file: &CodeFile) -> ViolationItem {
+        ViolationItem {
+            kind: ElementKind::File,
+            name: file.package.clone(),
+            path: file.path.clone(),
+            start: Default::default(),
+            end: Default::default(),
+            import: None,
+        }
+    }
+
+    pub fn class(file: &CodeFile, class: &CodeClass) -> ViolationItem {
+        ViolationItem {
+            kind: ElementKind::Class,
+            name: qualified_name(&class.package, &class.name),
+            path: file.path.clone(),
+            start: class.start.clone(),
+            end: class.end.clone(),
+            import: None,
+        }
+    }
+
+    /// functions outside classes are named by package of file
+    pub fn function(file: &CodeFile, class: Option<&CodeClass>, function: &CodeFunction) -> ViolationItem {
+        let parent = match class {
+            Some(class) => qualified_name(&class.package, &class.name),
+            None => file.package.clone()
+        };
+
+        ViolationItem {
+            kind: ElementKind::Function,
+            name: qualified_name(&parent, &function.name),
+            path: file.path.clone(),
+            start: function.start.clone(),
+            end: function.end.clone(),
+            import: None,
+        }
+    }
+
+    /// fields have no location, so the location of class is used
+    pub fn field(file: &CodeFile, class: &CodeClass, field: &ClassConstant) -> ViolationItem {
+        ViolationItem {
+            kind: ElementKind::Field,
+            name: qualified_name(&qualified_name(&class.package, &class.name), &field.name),
+            path: file.path.clone(),
+            start: class.start.clone(),
+            end: class.end.clone(),
+            import: None,
+        }
+    }
+
+    pub fn with_import(mut self, import: &str) -> ViolationItem {
+        self.import = Some(import.to_string());
+        self
+    }
+}
+
+fn qualified_name(package: &str, name: &str) -> String {
+    if package.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", package, name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::code_class::CodeClass;
+    use crate::domain::code_file::CodeFile;
+    use crate::domain::CodePoint;
+    use crate::rule_executor::rule_error::{ElementKind, ViolationItem};
+
+    #[test]
+    fn should_build_class_item_with_location() {
+        let file = CodeFile {
+            path: "src/parser/JsonParser.java".to_string(),
+            ..Default::default()
+        };
+
+        let class = CodeClass {
+            package: "com.phodal.parser".to_string(),
+            name: "JsonParser".to_string(),
+            start: CodePoint { row: 3, column: 0 },
+            end: CodePoint { row: 20, column: 0 },
+            ..Default::default()
+        };
+
+        let item = ViolationItem::class(&file, &class);
+
+        assert_eq!(ElementKind::Class, item.kind);
+        assert_eq!("com.phodal.parser.JsonParser", item.name);
+        assert_eq!("src/parser/JsonParser.java", item.path);
+        assert_eq!(3, item.start.row);
+        assert_eq!(20, item.end.row);
+        assert_eq!(None, item.import);
+    }
+
+    #[test]
+    fn should_serialize_kind_in_lowercase() {
+        let item = ViolationItem::file(&CodeFile::default()).with_import("java.util.Map");
+        let json = serde_json::to_string(&item).unwrap();
+
+        assert!(json.contains("\"kind\":\"file\""));
+        assert!(json.contains("\"import\":\"java.util.Map\""));
+    }
+}
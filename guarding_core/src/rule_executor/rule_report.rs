@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+
+use guarding_parser::ast::GuardRule;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleStatus {
+    Passed,
+    Failed,
+    /// nothing is selected by the rule, so nothing is checked
+    Empty,
+    /// no evaluator for the level, property and operator of the rule
+    Unsupported,
+}
+
+/// Result of a single rule, for passed rules too.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RuleReport {
+    /// rule origin with whitespaces collapsed
+    pub id: String,
+    pub rule_index: usize,
+    pub status: RuleStatus,
+    /// elements selected by level and scope
+    pub selected: usize,
+    /// elements checked by the assert
+    pub asserted: usize,
+    /// violation errors of the rule
+    pub errors: usize,
+    pub elapsed_ms: f64,
+}
+
+impl RuleReport {
+    pub fn new(rule: &GuardRule, index: usize) -> RuleReport {
+        RuleReport {
+            id: rule_id(rule, index),
+            rule_index: index,
+            status: RuleStatus::Passed,
+            selected: 0,
+            asserted: 0,
+            errors: 0,
+            elapsed_ms: 0.0,
+        }
+    }
+
+    /// update `errors` and the failed/passed status, like after violations are accepted by baseline.
+    /// a rule which selects nothing is never passed
+    pub fn set_errors(&mut self, errors: usize) {
+        self.errors = errors;
+        match self.status {
+            RuleStatus::Failed if errors == 0 && self.selected == 0 => self.status = RuleStatus::Empty,
+            RuleStatus::Failed if errors == 0 => self.status = RuleStatus::Passed,
+            RuleStatus::Passed | RuleStatus::Empty if errors > 0 => self.status = RuleStatus::Failed,
+            _ => {}
+        }
+    }
+}
+
+/// stable id of a rule, the index is only used when origin is unknown
+pub fn rule_id(rule: &GuardRule, index: usize) -> String {
+    if rule.origin.is_empty() {
+        return format!("#{}", index);
+    }
+
+    rule.origin.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use guarding_parser::parser;
+
+    use crate::rule_executor::rule_report::{rule_id, RuleReport, RuleStatus};
+
+    #[test]
+    fn should_collapse_whitespaces_in_id() {
+        let rules = parser::parse("class(\".\")::name   should\n endsWith \"Parser\";").unwrap();
+        assert_eq!("class(\".\")::name should endsWith \"Parser\";", rule_id(&rules[0], 0));
+    }
+
+    #[test]
+    fn should_update_status_by_errors() {
+        let rules = parser::parse("class(\".\")::len should < 20;").unwrap();
+        let mut report = RuleReport::new(&rules[0], 0);
+        report.status = RuleStatus::Failed;
+        report.selected = 1;
+
+        report.set_errors(0);
+        assert_eq!(RuleStatus::Passed, report.status);
+
+        report.set_errors(2);
+        assert_eq!(RuleStatus::Failed, report.status);
+    }
+
+    #[test]
+    fn should_not_pass_rule_which_selects_nothing() {
+        let rules = parser::parse("class(\"..controler..\")::len should < 20;").unwrap();
+        let mut report = RuleReport::new(&rules[0], 0);
+        report.status = RuleStatus::Failed;
+
+        report.set_errors(0);
+        assert_eq!(RuleStatus::Empty, report.status);
+    }
+}
@@ -1,8 +1,8 @@
 use tree_sitter::{Node, Parser, Query, QueryCursor};
 use guarding_core::domain::code_class::CodeClass;
 use guarding_core::domain::code_file::CodeFile;
-use guarding_core::domain::code_function::CodeFunction;
 use crate::code_ident::CodeIdent;
+use crate::identify::complexity::BranchKinds;
 
 const C_SHARP_QUERY: &'static str = "
 (using_directive
@@ -27,6 +27,12 @@ const C_SHARP_QUERY: &'static str = "
     ) @body
 )";
 
+const C_SHARP_BRANCHES: BranchKinds = BranchKinds {
+    decisions: &["if_statement", "for_statement", "for_each_statement", "while_statement", "do_statement", "catch_clause", "conditional_expression", "case_switch_label", "case_pattern_switch_label", "switch_expression_arm"],
+    nestings: &["if_statement", "for_statement", "for_each_statement", "while_statement", "do_statement", "switch_statement", "try_statement"],
+    boundaries: &["class_declaration", "method_declaration", "local_function_statement"],
+};
+
 
 pub struct CSharpIdent {
     parser: Parser,
@@ -50,7 +56,7 @@ impl CSharpIdent {
 
     fn do_parse(code: &&str, ident: &mut CSharpIdent) -> CodeFile {
         let tree = ident.parser.parse(code, None).unwrap();
-        let text_callback = |n: Node| &code[n.byte_range()];
+        let text_callback = |n: Node| std::iter::once(code[n.byte_range()].as_bytes());
         let mut query_cursor = QueryCursor::new();
         let captures = query_cursor.captures(&ident.query, tree.root_node(), text_callback);
 
@@ -80,9 +86,7 @@ impl CSharpIdent {
                     class.implements.push(text.to_string());
                 }
                 "method-name" => {
-                    let mut function = CodeFunction::default();
-                    function.name = text.to_string();
-                    class.functions.push(function);
+                    class.functions.push(CSharpIdent::create_function(capture, text));
 
                     if !class.name.is_empty() {
                         code_file.classes.push(class.clone());
@@ -105,11 +109,17 @@ impl CSharpIdent {
             code_file.classes.push(class.clone());
         }
 
+        CSharpIdent::insert_lines(&mut code_file, tree.root_node(), code);
+
         code_file
     }
 }
 
 impl CodeIdent for CSharpIdent {
+    fn branch_kinds() -> &'static BranchKinds {
+        &C_SHARP_BRANCHES
+    }
+
     fn parse(code: &str) -> CodeFile {
         let mut ident = CSharpIdent::new();
         CSharpIdent::do_parse(&code, &mut ident)
@@ -1,27 +1,67 @@
+use std::collections::BTreeSet;
+
 use tree_sitter::{Node, QueryCapture};
 
 use guarding_core::domain::code_function::CodeFunction;
 use guarding_core::domain::code_file::CodeFile;
 use guarding_core::domain::Location;
 
+use crate::identify::complexity::{self, BranchKinds};
+
 pub trait CodeIdent {
     fn parse(code: &str) -> CodeFile;
 
+    /// node kinds for measuring complexity and nesting of functions
+    fn branch_kinds() -> &'static BranchKinds;
+
     fn insert_location<T: Location>(model: &mut T, node: Node) {
         model.set_start(node.start_position().row, node.start_position().column);
         model.set_end(node.end_position().row, node.end_position().column);
     }
 
-    fn create_function( capture: QueryCapture, text: &str) -> CodeFunction {
-        let mut function = CodeFunction::default();
-        function.name = text.to_string();
+    /// `lines` of text, and `loc` which skips blank and comment lines
+    fn insert_lines(file: &mut CodeFile, root: Node, code: &str) {
+        fn mark_code_rows(node: Node, rows: &mut BTreeSet<usize>) {
+            if node.kind().contains("comment") {
+                return;
+            }
+
+            if node.child_count() == 0 {
+                let (start, end) = (node.start_position(), node.end_position());
+                let last = if end.column == 0 && end.row > start.row { end.row - 1 } else { end.row };
+                rows.extend(start.row..=last);
+                return;
+            }
+
+            let mut cursor = node.walk();
+            for child in node.children(&mut cursor) {
+                mark_code_rows(child, rows);
+            }
+        }
 
+        let mut rows = BTreeSet::new();
+        mark_code_rows(root, &mut rows);
+
+        file.lines = code.lines().count();
+        file.loc = rows.len();
+    }
+
+    fn create_function( capture: QueryCapture, text: &str) -> CodeFunction {
         let node = capture.node.parent().unwrap();
-        // RustIdent::insert_location(&mut function, node);
+        Self::create_function_by_node(node, text)
+    }
+
+    /// `node` is the whole function, like `method_declaration` in Java
+    fn create_function_by_node(node: Node, name: &str) -> CodeFunction {
+        let mut function = CodeFunction {
+            name: name.to_string(),
+            ..Default::default()
+        };
 
         function.set_start(node.start_position().row, node.start_position().column);
         function.set_end(node.end_position().row, node.end_position().column);
+        complexity::measure(&mut function, node, Self::branch_kinds());
 
         function
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,108 @@
+use tree_sitter::Node;
+
+use guarding_core::domain::code_function::CodeFunction;
+
+const LOGICAL_OPERATORS: [&str; 3] = ["&&", "||", "??"];
+
+/// Node kinds of a language grammar which are used for measuring functions.
+pub struct BranchKinds {
+    /// nodes which add a path, like `if`, loops, `catch` and `case`, a `default` or `else` case is skipped
+    pub decisions: &'static [&'static str],
+    /// nodes which increase nesting depth, an `else if` stays in the depth of its `if`
+    pub nestings: &'static [&'static str],
+    /// nested functions and classes, they are measured by themselves
+    pub boundaries: &'static [&'static str],
+}
+
+/// cyclomatic complexity and max nesting depth of `node`, the body of `function`
+pub fn measure(function: &mut CodeFunction, node: Node, kinds: &BranchKinds) {
+    let mut complexity = 1;
+    let mut nesting = 0;
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        visit(child, kinds, 0, &mut complexity, &mut nesting);
+    }
+
+    function.complexity = complexity;
+    function.nesting = nesting;
+}
+
+fn visit(node: Node, kinds: &BranchKinds, depth: usize, complexity: &mut usize, nesting: &mut usize) {
+    let kind = node.kind();
+    if kinds.boundaries.contains(&kind) {
+        return;
+    }
+
+    if kinds.decisions.contains(&kind) && !is_default_case(node) {
+        *complexity += 1;
+    }
+
+    if is_match_guard(node) {
+        *complexity += 1;
+    }
+
+    if kind.ends_with("binary_expression") && has_logical_operator(node) {
+        *complexity += 1;
+    }
+
+    let mut depth = depth;
+    if kinds.nestings.contains(&kind) && !is_else_if(node, kinds) {
+        depth += 1;
+        if depth > *nesting {
+            *nesting = depth;
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        visit(child, kinds, depth, complexity, nesting);
+    }
+}
+
+fn is_default_case(node: Node) -> bool {
+    if node.kind() == "match_arm" {
+        return is_wildcard_arm(node);
+    }
+
+    matches!(node.child(0), Some(child) if child.kind() == "default" || child.kind() == "else")
+}
+
+/// `_ => {}` arm of Rust `match`, its guard is counted by itself
+fn is_wildcard_arm(node: Node) -> bool {
+    let pattern = node.child_by_field_name("pattern");
+    matches!(pattern.and_then(|pattern| pattern.child(0)), Some(child) if child.kind() == "_")
+}
+
+/// `if` guard of Rust match arm, like `Some(x) if x > 0 => x`
+fn is_match_guard(node: Node) -> bool {
+    node.kind() == "match_pattern" && node.child_by_field_name("condition").is_some()
+}
+
+fn has_logical_operator(node: Node) -> bool {
+    let mut cursor = node.walk();
+    let has_operator = node.children(&mut cursor)
+        .any(|child| !child.is_named() && LOGICAL_OPERATORS.contains(&child.kind()));
+    has_operator
+}
+
+/// `else if` is an `if` inside the alternative of another `if`, directly or by an `else_clause`
+fn is_else_if(node: Node, kinds: &BranchKinds) -> bool {
+    if !node.kind().starts_with("if_") {
+        return false;
+    }
+
+    let (mut alternative, mut parent) = (node, node.parent());
+    if let Some(clause) = parent {
+        if clause.kind() == "else_clause" {
+            alternative = clause;
+            parent = clause.parent();
+        }
+    }
+
+    match parent {
+        Some(parent) => kinds.nestings.contains(&parent.kind())
+            && parent.child_by_field_name("alternative") == Some(alternative),
+        None => false
+    }
+}
@@ -0,0 +1,474 @@
+use tree_sitter::{Node, Parser, Query, QueryCursor};
+
+use guarding_core::domain::code_class::{ClassConstant, CodeClass};
+use guarding_core::domain::code_file::CodeFile;
+use guarding_core::domain::code_function::CodeFunction;
+use crate::code_ident::CodeIdent;
+use crate::identify::complexity::BranchKinds;
+
+const CPP_QUERY: &str = "
+(preproc_include
+	path: (_) @include)
+
+(namespace_definition) @namespace
+
+(class_specifier
+	body: (field_declaration_list)) @class
+(struct_specifier
+	body: (field_declaration_list)) @class
+
+(function_definition) @function
+";
+
+const CPP_BRANCHES: BranchKinds = BranchKinds {
+    decisions: &["if_statement", "for_statement", "for_range_loop", "while_statement", "do_statement", "catch_clause", "conditional_expression", "case_statement"],
+    nestings: &["if_statement", "for_statement", "for_range_loop", "while_statement", "do_statement", "switch_statement", "try_statement"],
+    boundaries: &["function_definition", "lambda_expression", "class_specifier", "struct_specifier"],
+};
+
+/// C and C++ ident, C is parsed by the C++ grammar. the package is the namespace of the
+/// first declaration, files without namespace get their directory from `ModelBuilder`
+pub struct CppIdent {
+    parser: Parser,
+    query: Query,
+}
+
+impl CppIdent {
+    fn new() -> CppIdent {
+        let mut parser = Parser::new();
+
+        let language = tree_sitter_cpp::language();
+        parser.set_language(language).unwrap();
+
+        let query = Query::new(language, CPP_QUERY)
+            .map_err(|e| println!("Query compilation failed: {:?}", e)).unwrap();
+        CppIdent { parser, query }
+    }
+}
+
+impl CppIdent {
+    fn do_parse(code: &str, ident: &mut CppIdent) -> CodeFile {
+        let text_callback = |n: Node| std::iter::once(code[n.byte_range()].as_bytes());
+        let tree = ident.parser.parse(code, None).unwrap();
+
+        let mut query_cursor = QueryCursor::new();
+        let captures = query_cursor.captures(&ident.query, tree.root_node(), text_callback);
+
+        let mut code_file = CodeFile::default();
+        let mut first_namespace = "".to_string();
+        // (class name, method) of `void Uart::send() {}`, the class may be declared after
+        let mut methods: Vec<(String, CodeFunction)> = vec![];
+
+        for (mat, capture_index) in captures {
+            let capture = mat.captures[capture_index];
+            let capture_name = &ident.query.capture_names()[capture.index as usize];
+
+            let text = capture.node.utf8_text((&code).as_ref()).unwrap_or("");
+            match capture_name.as_str() {
+                "include" => {
+                    // `"drivers/uart.h"` or `<stdio.h>`
+                    code_file.imports.push(text.trim_matches(|c| c == '"' || c == '<' || c == '>').to_string());
+                }
+                "namespace" => {
+                    if first_namespace.is_empty() {
+                        first_namespace = CppIdent::namespace_of(capture.node, code);
+                        if let Some(name) = capture.node.child_by_field_name("name") {
+                            first_namespace = CppIdent::join_namespace(&first_namespace, &code[name.byte_range()]);
+                        }
+                    }
+                }
+                "class" => {
+                    if let Some(class) = CppIdent::create_class(capture.node, code) {
+                        code_file.classes.push(class);
+                    }
+                }
+                "function" => {
+                    if !CppIdent::is_declaration_scope(capture.node.parent()) {
+                        continue;
+                    }
+
+                    if let Some((scope, function)) = CppIdent::create_cpp_function(capture.node, code) {
+                        match scope {
+                            Some(class_name) => methods.push((class_name, function)),
+                            None => code_file.functions.push(function),
+                        }
+                    }
+                }
+                &_ => {}
+            }
+        }
+
+        // a definition replaces its declaration in class, methods of classes which are declared
+        // in other files, like headers, stay as functions
+        let mut defined: Vec<(usize, usize)> = vec![];
+        for (class_name, method) in methods {
+            let class_index = match code_file.classes.iter().position(|class| class.name == class_name) {
+                Some(index) => index,
+                None => {
+                    code_file.functions.push(method);
+                    continue;
+                }
+            };
+
+            let functions = &mut code_file.classes[class_index].functions;
+            let declared = (0..functions.len())
+                .find(|i| functions[*i].name == method.name && !defined.contains(&(class_index, *i)));
+            match declared {
+                Some(i) => {
+                    functions[i] = method;
+                    defined.push((class_index, i));
+                }
+                None => functions.push(method),
+            }
+        }
+
+        code_file.package = code_file.classes.iter()
+            .map(|class| class.package.clone())
+            .find(|package| !package.is_empty())
+            .unwrap_or(first_namespace);
+
+        CppIdent::insert_lines(&mut code_file, tree.root_node(), code);
+
+        code_file
+    }
+
+    /// class or struct of file or namespace scope, nested classes are skipped
+    fn create_class(class_node: Node, code: &str) -> Option<CodeClass> {
+        let mut parent = class_node.parent();
+        // `typedef struct { .. } Point;` in C
+        let mut typedef_name = None;
+        if let Some(typedef) = parent.filter(|node| node.kind() == "type_definition") {
+            typedef_name = typedef.child_by_field_name("declarator").map(|name| &code[name.byte_range()]);
+            parent = typedef.parent();
+        }
+
+        if !CppIdent::is_declaration_scope(parent) {
+            return None;
+        }
+
+        let name = match class_node.child_by_field_name("name") {
+            Some(name) => &code[name.byte_range()],
+            None => typedef_name?,
+        };
+
+        let mut class = CodeClass {
+            name: name.to_string(),
+            package: CppIdent::namespace_of(class_node, code).replace("::", "."),
+            ..Default::default()
+        };
+        CppIdent::insert_location(&mut class, class_node);
+
+        let mut cursor = class_node.walk();
+        for child in class_node.children(&mut cursor) {
+            match child.kind() {
+                "base_class_clause" => {
+                    let mut cursor = child.walk();
+                    for base in child.named_children(&mut cursor).filter(|base| base.kind() != "access_specifier") {
+                        let text = &code[base.byte_range()];
+                        class.extends.push(text.split('<').next().unwrap_or(text).trim().to_string());
+                    }
+                }
+                "field_declaration_list" => {
+                    CppIdent::insert_members(&mut class, child, code);
+                }
+                _ => {}
+            }
+        }
+
+        Some(class)
+    }
+
+    /// methods and fields, a class with pure virtual methods is abstract
+    fn insert_members(class: &mut CodeClass, body: Node, code: &str) {
+        let mut cursor = body.walk();
+        for child in body.named_children(&mut cursor) {
+            let member = match child.kind() {
+                "template_declaration" => {
+                    let mut cursor = child.walk();
+                    let member = child.named_children(&mut cursor).last();
+                    match member {
+                        Some(member) => member,
+                        None => continue,
+                    }
+                }
+                _ => child
+            };
+
+            match member.kind() {
+                "function_definition" => {
+                    if let Some((_, function)) = CppIdent::create_cpp_function(member, code) {
+                        class.functions.push(function);
+                    }
+                }
+                "field_declaration" | "declaration" => {
+                    let declarator = match member.child_by_field_name("declarator") {
+                        Some(declarator) => declarator,
+                        None => continue,
+                    };
+
+                    match CppIdent::function_declarator(declarator) {
+                        Some(function_declarator) => {
+                            let name = CppIdent::function_name(function_declarator, code);
+                            class.functions.push(CppIdent::create_function_by_node(member, &name));
+
+                            let is_pure = member.child_by_field_name("default_value")
+                                .map(|value| &code[value.byte_range()] == "0")
+                                .unwrap_or(false);
+                            class.is_abstract = class.is_abstract || is_pure;
+                        }
+                        None => {
+                            let typ = member.child_by_field_name("type")
+                                .map(|typ| code[typ.byte_range()].to_string())
+                                .unwrap_or_default();
+                            let is_constant = CppIdent::has_specifier(member, code, "const")
+                                || CppIdent::has_specifier(member, code, "constexpr");
+
+                            let mut cursor = member.walk();
+                            for declarator in member.children_by_field_name("declarator", &mut cursor) {
+                                let name = CppIdent::declared_name(declarator, code);
+                                class.constant.push(ClassConstant { name, typ: typ.clone(), is_constant });
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// function, and the class of `Uart::send` if it's defined outside of class
+    fn create_cpp_function(function_node: Node, code: &str) -> Option<(Option<String>, CodeFunction)> {
+        let declarator = function_node.child_by_field_name("declarator")?;
+        let function_declarator = CppIdent::function_declarator(declarator)?;
+        let name = CppIdent::function_name(function_declarator, code);
+
+        let function = CppIdent::create_function_by_node(function_node, &name);
+        // `Uart` of `app::Uart::send`
+        let mut scope = None;
+        let mut qualified = function_declarator.child_by_field_name("declarator");
+        while let Some(node) = qualified.filter(|node| node.kind() == "qualified_identifier") {
+            if let Some(namespace) = node.child_by_field_name("scope") {
+                scope = Some(code[namespace.byte_range()].to_string());
+            }
+            qualified = node.child_by_field_name("name");
+        }
+
+        Some((scope, function))
+    }
+
+    /// `send` of `send(int)`, `Uart::send(int)` or `*create()`
+    fn function_name(function_declarator: Node, code: &str) -> String {
+        let mut name = match function_declarator.child_by_field_name("declarator") {
+            Some(name) => name,
+            None => { return "".to_string(); }
+        };
+
+        while name.kind() == "qualified_identifier" {
+            match name.child_by_field_name("name") {
+                Some(inner) => name = inner,
+                None => break,
+            }
+        }
+
+        code[name.byte_range()].to_string()
+    }
+
+    /// `function_declarator` inside pointer and reference declarators
+    fn function_declarator(declarator: Node) -> Option<Node> {
+        let mut node = declarator;
+        loop {
+            if node.kind() == "function_declarator" {
+                return Some(node);
+            }
+
+            node = match node.child_by_field_name("declarator") {
+                Some(inner) => inner,
+                None => {
+                    let mut cursor = node.walk();
+                    let inner = node.named_children(&mut cursor)
+                        .find(|child| child.kind().ends_with("declarator"));
+                    inner?
+                }
+            };
+        }
+    }
+
+    /// `size` of `*size`, `data[10]` or `count = 0`
+    fn declared_name(declarator: Node, code: &str) -> String {
+        let mut node = declarator;
+        while let Some(inner) = node.child_by_field_name("declarator") {
+            node = inner;
+        }
+
+        let mut cursor = node.walk();
+        let identifier = node.named_children(&mut cursor)
+            .find(|child| child.kind().ends_with("identifier"));
+        let name = identifier.unwrap_or(node);
+        code[name.byte_range()].to_string()
+    }
+
+    /// file, namespace, `extern "C"` or template scope
+    fn is_declaration_scope(parent: Option<Node>) -> bool {
+        let mut parent = parent;
+        while let Some(node) = parent {
+            match node.kind() {
+                "translation_unit" => { return true; }
+                "declaration_list" | "namespace_definition" | "linkage_specification" | "template_declaration" => {
+                    parent = node.parent();
+                }
+                _ => { return false; }
+            }
+        }
+
+        false
+    }
+
+    /// `app::drivers` of declarations inside `namespace app { namespace drivers { .. } }`
+    fn namespace_of(node: Node, code: &str) -> String {
+        let mut names = vec![];
+        let mut parent = node.parent();
+        while let Some(current) = parent {
+            if current.kind() == "namespace_definition" {
+                if let Some(name) = current.child_by_field_name("name") {
+                    names.push(&code[name.byte_range()]);
+                }
+            }
+            parent = current.parent();
+        }
+
+        names.reverse();
+        names.join("::")
+    }
+
+    fn join_namespace(namespace: &str, name: &str) -> String {
+        let joined = if namespace.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}::{}", namespace, name)
+        };
+        joined.replace("::", ".")
+    }
+
+    fn has_specifier(node: Node, code: &str, specifier: &str) -> bool {
+        let mut cursor = node.walk();
+        let has_specifier = node.children(&mut cursor)
+            .filter(|child| child.kind() == "type_qualifier" || child.kind() == "storage_class_specifier")
+            .any(|child| &code[child.byte_range()] == specifier);
+        has_specifier
+    }
+}
+
+impl CodeIdent for CppIdent {
+    fn branch_kinds() -> &'static BranchKinds {
+        &CPP_BRANCHES
+    }
+
+    fn parse(code: &str) -> CodeFile {
+        let mut ident = CppIdent::new();
+        CppIdent::do_parse(code, &mut ident)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::code_ident::CodeIdent;
+    use crate::cpp_ident::CppIdent;
+
+    #[test]
+    fn should_parse_includes_as_imports() {
+        let source_code = "#include <stdio.h>
+#include \"drivers/uart.h\"
+#include \"../ui/button.hpp\"
+";
+        let file = CppIdent::parse(source_code);
+
+        assert_eq!(vec!["stdio.h", "drivers/uart.h", "../ui/button.hpp"], file.imports);
+    }
+
+    #[test]
+    fn should_parse_class_in_namespace() {
+        let source_code = "namespace app {
+namespace drivers {
+
+class Uart : public Device, private Stream<char> {
+public:
+    static const int MAX_SIZE = 20;
+    int baud, *buffer;
+
+    Uart();
+    virtual void open() = 0;
+    int read() const { return 0; }
+    Device *device();
+};
+
+}
+}
+";
+        let file = CppIdent::parse(source_code);
+
+        assert_eq!("app.drivers", file.package);
+        let class = &file.classes[0];
+        assert_eq!("Uart", class.name);
+        assert_eq!("app.drivers", class.package);
+        assert_eq!(vec!["Device", "Stream"], class.extends);
+        assert!(class.is_abstract);
+
+        let functions: Vec<&str> = class.functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(vec!["Uart", "open", "read", "device"], functions);
+
+        let fields: Vec<&str> = class.constant.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(vec!["MAX_SIZE", "baud", "buffer"], fields);
+        assert!(class.constant[0].is_constant);
+    }
+
+    #[test]
+    fn should_parse_c_struct_and_functions() {
+        let source_code = "typedef struct {
+    int x;
+} Point;
+
+struct Node {
+    struct Node *next;
+};
+
+static int parse(const char *text) {
+    switch (*text) {
+    case 'a':
+        return 1;
+    default:
+        return text[0] > 0 && text[1] > 0 ? 2 : 0;
+    }
+}
+";
+        let file = CppIdent::parse(source_code);
+
+        assert_eq!("", file.package);
+        let classes: Vec<&str> = file.classes.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(vec!["Point", "Node"], classes);
+        assert_eq!("next", file.classes[1].constant[0].name);
+
+        assert_eq!("parse", file.functions[0].name);
+        assert_eq!(4, file.functions[0].complexity);
+    }
+
+    #[test]
+    fn should_attach_methods_defined_outside_class() {
+        let source_code = "class Uart {
+    void send(int data);
+};
+
+void Uart::send(int data) {
+    if (data) {}
+}
+
+int main() { return 0; }
+";
+        let file = CppIdent::parse(source_code);
+
+        let functions: Vec<&str> = file.classes[0].functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(vec!["send"], functions);
+        assert_eq!(2, file.classes[0].functions[0].complexity);
+        assert_eq!(4, file.classes[0].functions[0].start.row);
+        assert_eq!("main", file.functions[0].name);
+    }
+}
@@ -0,0 +1,370 @@
+use tree_sitter::{Node, Parser, Query, QueryCursor};
+
+use guarding_core::domain::code_file::CodeFile;
+use guarding_core::domain::code_function::CodeFunction;
+use guarding_core::domain::code_import::CodeImport;
+use guarding_core::domain::code_class::{ClassConstant, CodeClass};
+use crate::code_ident::CodeIdent;
+use crate::identify::complexity::BranchKinds;
+
+const GO_QUERY: &str = "
+(package_clause
+	(package_identifier) @package-name)
+
+(import_spec
+	path: (_) @import-path)
+
+(source_file
+    (type_declaration
+        (type_spec
+            name: (type_identifier) @type-name
+        )
+    )
+)
+
+(source_file
+    (function_declaration
+        name: (identifier) @function-name
+    )
+)
+
+(source_file
+    (method_declaration
+        name: (field_identifier) @method-name
+    )
+)
+";
+
+const GO_BRANCHES: BranchKinds = BranchKinds {
+    decisions: &["if_statement", "for_statement", "expression_case", "type_case", "communication_case"],
+    nestings: &["if_statement", "for_statement", "expression_switch_statement", "type_switch_statement", "select_statement"],
+    boundaries: &["function_declaration", "method_declaration", "func_literal"],
+};
+
+pub struct GoIdent {
+    parser: Parser,
+    query: Query
+}
+
+impl GoIdent {
+    fn new() -> GoIdent {
+        let mut parser = Parser::new();
+
+        let language = tree_sitter_go::language();
+        parser.set_language(language).unwrap();
+
+        let query = Query::new(language, GO_QUERY)
+            .map_err(|e| println!("Query compilation failed: {:?}", e)).unwrap();
+        GoIdent { parser, query }
+    }
+}
+
+impl GoIdent {
+    fn do_parse(code: &str, ident: &mut GoIdent) -> CodeFile {
+        let text_callback = |n: Node| std::iter::once(code[n.byte_range()].as_bytes());
+        let tree = ident.parser.parse(code, None).unwrap();
+
+        let mut query_cursor = QueryCursor::new();
+        let captures = query_cursor.captures(&ident.query, tree.root_node(), text_callback);
+
+        let mut code_file = CodeFile::default();
+        // (receiver type, method), a type may be declared after its methods
+        let mut methods: Vec<(String, CodeFunction)> = vec![];
+
+        for (mat, capture_index) in captures {
+            let capture = mat.captures[capture_index];
+            let capture_name = &ident.query.capture_names()[capture.index as usize];
+
+            let text = capture.node.utf8_text((&code).as_ref()).unwrap_or("");
+            match capture_name.as_str() {
+                "package-name" => {
+                    code_file.package = text.to_string();
+                }
+                "import-path" => {
+                    // aliased, blank and dot imports still depend on the path
+                    let import = GoIdent::create_import(capture.node, text, code);
+                    code_file.imports.push(import.import.clone());
+                    code_file.code_imports.push(import);
+                }
+                "type-name" => {
+                    let spec_node = capture.node.parent().unwrap();
+                    if let Some(class) = GoIdent::create_class(spec_node, text, code) {
+                        code_file.classes.push(class);
+                    }
+                }
+                "function-name" => {
+                    code_file.functions.push(GoIdent::create_function(capture, text));
+                }
+                "method-name" => {
+                    let method_node = capture.node.parent().unwrap();
+                    let receiver = GoIdent::receiver_type(method_node, code);
+                    methods.push((receiver, GoIdent::create_function(capture, text)));
+                }
+                &_ => {}
+            }
+        }
+
+        for class in code_file.classes.iter_mut() {
+            class.package = code_file.package.clone();
+        }
+
+        // methods of types which aren't struct or declared in other files stay as functions
+        for (receiver, method) in methods {
+            match code_file.classes.iter_mut().find(|class| class.name == receiver) {
+                Some(class) => class.functions.push(method),
+                None => code_file.functions.push(method),
+            }
+        }
+
+        GoIdent::insert_lines(&mut code_file, tree.root_node(), code);
+
+        code_file
+    }
+
+    /// `log` of `log "github.com/sirupsen/logrus"` is the alias, a dot import, like
+    /// `. "github.com/app/domain"`, imports every name of the package
+    fn create_import(path_node: Node, text: &str, code: &str) -> CodeImport {
+        let path = text.trim_matches(|c| c == '"' || c == '`');
+        let spec = path_node.parent().unwrap_or(path_node);
+        let alias = spec.child_by_field_name("name").map(|name| code[name.byte_range()].to_string());
+
+        CodeImport {
+            name: path.replace('/', "."),
+            import: path.to_string(),
+            source: code[spec.byte_range()].to_string(),
+            is_wildcard: alias.as_deref() == Some("."),
+            alias,
+            line: spec.start_position().row,
+            ..Default::default()
+        }
+    }
+
+    /// struct or interface of `type_spec`, other types like `type Level int` are skipped
+    fn create_class(spec_node: Node, name: &str, code: &str) -> Option<CodeClass> {
+        let type_node = spec_node.child_by_field_name("type")?;
+
+        let mut class = CodeClass {
+            name: name.to_string(),
+            ..Default::default()
+        };
+        GoIdent::insert_location(&mut class, spec_node);
+
+        match type_node.kind() {
+            "struct_type" => GoIdent::insert_fields(&mut class, type_node, code),
+            "interface_type" => {
+                class.is_abstract = true;
+                GoIdent::insert_method_specs(&mut class, type_node, code);
+            }
+            _ => { return None; }
+        }
+
+        Some(class)
+    }
+
+    /// named fields, embedded types are recorded as `extends`
+    fn insert_fields(class: &mut CodeClass, struct_node: Node, code: &str) {
+        let mut cursor = struct_node.walk();
+        for list in struct_node.children(&mut cursor) {
+            let mut cursor = list.walk();
+            for field in list.children(&mut cursor).filter(|child| child.kind() == "field_declaration") {
+                let typ = field.child_by_field_name("type")
+                    .map(|typ| code[typ.byte_range()].to_string())
+                    .unwrap_or_default();
+
+                let mut cursor = field.walk();
+                let names: Vec<String> = field.children_by_field_name("name", &mut cursor)
+                    .filter(|name| name.kind() == "field_identifier")
+                    .map(|name| code[name.byte_range()].to_string())
+                    .collect();
+
+                if names.is_empty() {
+                    class.extends.push(typ.trim_start_matches('*').to_string());
+                }
+
+                for name in names {
+                    class.constant.push(ClassConstant { name, typ: typ.clone(), is_constant: false });
+                }
+            }
+        }
+    }
+
+    /// methods of interface, embedded interfaces are recorded as `extends`
+    fn insert_method_specs(class: &mut CodeClass, interface_node: Node, code: &str) {
+        let mut cursor = interface_node.walk();
+        for child in interface_node.named_children(&mut cursor) {
+            match child.kind() {
+                "method_spec" => {
+                    if let Some(name) = child.child_by_field_name("name") {
+                        class.functions.push(GoIdent::create_function_by_node(child, &code[name.byte_range()]));
+                    }
+                }
+                "constraint_elem" => {
+                    class.extends.push(code[child.byte_range()].to_string());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// `Service` of `func (s *Service) Run()`
+    fn receiver_type(method_node: Node, code: &str) -> String {
+        fn find_type_identifier(node: Node) -> Option<Node> {
+            if node.kind() == "type_identifier" {
+                return Some(node);
+            }
+
+            let mut cursor = node.walk();
+            let found = node.children(&mut cursor).find_map(find_type_identifier);
+            found
+        }
+
+        method_node.child_by_field_name("receiver")
+            .and_then(|receiver| {
+                let mut cursor = receiver.walk();
+                let param = receiver.named_children(&mut cursor).find(|child| child.kind() == "parameter_declaration");
+                param
+            })
+            .and_then(|param| param.child_by_field_name("type"))
+            .and_then(find_type_identifier)
+            .map(|typ| code[typ.byte_range()].to_string())
+            .unwrap_or_default()
+    }
+}
+
+impl CodeIdent for GoIdent {
+    fn branch_kinds() -> &'static BranchKinds {
+        &GO_BRANCHES
+    }
+
+    fn parse(code: &str) -> CodeFile {
+        let mut ident = GoIdent::new();
+        GoIdent::do_parse(code, &mut ident)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::code_ident::CodeIdent;
+    use crate::go_ident::GoIdent;
+
+    #[test]
+    fn should_parse_package_and_imports() {
+        let source_code = "package service
+
+import \"fmt\"
+
+import (
+    log \"github.com/sirupsen/logrus\"
+    _ \"github.com/lib/pq\"
+    . \"github.com/inherd/app/domain\"
+)
+";
+        let file = GoIdent::parse(source_code);
+
+        assert_eq!("service", file.package);
+        assert_eq!(vec!["fmt", "github.com/sirupsen/logrus", "github.com/lib/pq", "github.com/inherd/app/domain"], file.imports);
+
+        let imports = &file.code_imports;
+        assert_eq!(None, imports[0].alias);
+        assert_eq!("github.com.sirupsen.logrus", imports[1].name);
+        assert_eq!(Some("log".to_string()), imports[1].alias);
+        assert_eq!(Some("_".to_string()), imports[2].alias);
+        assert!(imports[3].is_wildcard);
+        assert_eq!(". \"github.com/inherd/app/domain\"", imports[3].source);
+    }
+
+    #[test]
+    fn should_parse_struct_with_methods() {
+        let source_code = "package service
+
+func (s *UserService) Find(id int) *User {
+    return nil
+}
+
+type UserService struct {
+    BaseService
+    repo, cache Repository
+    name string `json:\"name\"`
+}
+
+func (s UserService) Name() string {
+    return s.name
+}
+
+func (l Level) String() string {
+    return \"\"
+}
+
+func NewUserService() *UserService {
+    return &UserService{}
+}
+";
+        let file = GoIdent::parse(source_code);
+
+        assert_eq!(1, file.classes.len());
+        let class = &file.classes[0];
+        assert_eq!("UserService", class.name);
+        assert_eq!("service", class.package);
+        assert_eq!(vec!["BaseService"], class.extends);
+        assert_eq!(3, class.constant.len());
+        assert_eq!("cache", class.constant[1].name);
+        assert_eq!("Repository", class.constant[1].typ);
+
+        let methods: Vec<&str> = class.functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(vec!["Find", "Name"], methods);
+
+        let functions: Vec<&str> = file.functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(vec!["NewUserService", "String"], functions);
+    }
+
+    #[test]
+    fn should_parse_interface() {
+        let source_code = "package domain
+
+type Repository interface {
+    io.Closer
+    Find(id int) (*User, error)
+    Save(user *User) error
+}
+";
+        let file = GoIdent::parse(source_code);
+
+        let class = &file.classes[0];
+        assert!(class.is_abstract);
+        assert_eq!(vec!["io.Closer"], class.extends);
+        assert_eq!(2, class.functions.len());
+        assert_eq!("Save", class.functions[1].name);
+    }
+
+    #[test]
+    fn should_measure_function_complexity() {
+        let source_code = "package main
+
+func level(text string) int {
+    switch text {
+    case \"a\":
+        return 1
+    case \"b\", \"c\":
+        if len(text) > 1 || text == \"\" {
+            return 2
+        }
+    default:
+        for _, c := range text {
+            if c == 'x' {
+                return 3
+            } else if c == 'y' {
+                return 4
+            }
+        }
+    }
+    return 0
+}
+";
+        let file = GoIdent::parse(source_code);
+        let function = &file.functions[0];
+
+        assert_eq!(8, function.complexity);
+        assert_eq!(3, function.nesting);
+        assert_eq!(21, file.lines);
+    }
+}
@@ -1,34 +1,35 @@
 use tree_sitter::{Node, Parser, Query, QueryCursor};
 
+use guarding_core::domain::code_annotation::CodeAnnotation;
 use guarding_core::domain::code_file::CodeFile;
-use guarding_core::domain::code_class::CodeClass;
+use guarding_core::domain::code_import::CodeImport;
+use guarding_core::domain::code_class::{ClassConstant, ClassKind, CodeClass};
 use crate::code_ident::CodeIdent;
+use crate::identify::complexity::BranchKinds;
 
-const JAVA_QUERY: &'static str = "
+const JAVA_QUERY: &str = "
 (package_declaration
 	(scoped_identifier) @package-name)
 
-(import_declaration
-	(scoped_identifier) @import-name)
-
-(method_declaration
-	(modifiers
-    	(annotation
-    		name: (identifier) @annotation.name
-            arguments: (annotation_argument_list)? @annotation.key_values
-    	)
-    )
-)
+(import_declaration) @import
 
 (program
-    (class_declaration
-	    name: (identifier) @class-name
-        interfaces: (super_interfaces (interface_type_list (type_identifier)  @impl-name))?
-    )
+    [
+        (class_declaration)
+        (interface_declaration)
+        (enum_declaration)
+        (record_declaration)
+        (annotation_type_declaration)
+    ] @class
 )
-
 ";
 
+const JAVA_BRANCHES: BranchKinds = BranchKinds {
+    decisions: &["if_statement", "for_statement", "enhanced_for_statement", "while_statement", "do_statement", "catch_clause", "ternary_expression", "switch_label"],
+    nestings: &["if_statement", "for_statement", "enhanced_for_statement", "while_statement", "do_statement", "switch_statement", "try_statement"],
+    boundaries: &["class_declaration", "interface_declaration", "enum_declaration", "record_declaration", "method_declaration", "constructor_declaration"],
+};
+
 pub struct JavaIdent {
     parser: Parser,
     query: Query
@@ -40,8 +41,8 @@ impl JavaIdent {
         let language = tree_sitter_java::language();
         parser.set_language(language).unwrap();
 
-        let query = Query::new(language, &JAVA_QUERY)
-            .map_err(|e| println!("{}", format!("Query compilation failed: {:?}", e))).unwrap();
+        let query = Query::new(language, JAVA_QUERY)
+            .map_err(|e| println!("Query compilation failed: {:?}", e)).unwrap();
 
         JavaIdent {
             parser,
@@ -53,13 +54,11 @@ impl JavaIdent {
 impl JavaIdent {
     fn do_parse(code: &&str, ident: &mut JavaIdent) -> CodeFile {
         let tree = ident.parser.parse(code, None).unwrap();
-        let text_callback = |n: Node| &code[n.byte_range()];
+        let text_callback = |n: Node| std::iter::once(code[n.byte_range()].as_bytes());
         let mut query_cursor = QueryCursor::new();
         let captures = query_cursor.captures(&ident.query, tree.root_node(), text_callback);
 
         let mut code_file = CodeFile::default();
-        let mut class = CodeClass::default();
-        let mut is_last_node = false;
 
         let capture_names = ident.query.capture_names();
 
@@ -67,54 +66,242 @@ impl JavaIdent {
             let capture = mat.captures[capture_index];
             let capture_name = &capture_names[capture.index as usize];
 
-            let text = capture.node.utf8_text((&code).as_ref()).unwrap_or("");
+            let text = capture.node.utf8_text(code.as_ref()).unwrap_or("");
             match capture_name.as_str() {
                 "package-name" => {
                     code_file.package = text.to_string();
                 }
-                "import-name" => {
-                    code_file.imports.push(text.to_string());
-                }
-                "class-name" => {
-                    if !class.name.is_empty() {
-                        code_file.classes.push(class.clone());
-                        class = CodeClass::default();
-                    }
-
-                    class.name = text.to_string();
-                    class.package = code_file.package.clone();
-
-                    let class_node = capture.node.parent().unwrap();
-                    JavaIdent::insert_location(&mut class, class_node);
-                    if !is_last_node {
-                        is_last_node = true;
-                    }
+                "import" => {
+                    let import = JavaIdent::create_import(capture.node, code);
+                    code_file.imports.push(import.import.clone());
+                    code_file.code_imports.push(import);
                 }
-                "impl-name" => {
-                    class.implements.push(text.to_string());
+                "class" => {
+                    let package = code_file.package.clone();
+                    JavaIdent::insert_classes(&mut code_file.classes, capture.node, "", &package, code);
                 }
-                "parameter" => {}
                 &_ => {
                     println!(
                         "    pattern: {}, capture: {}, row: {}, text: {:?}",
                         mat.pattern_index,
                         capture_name,
                         capture.node.start_position().row,
-                        capture.node.utf8_text((&code).as_ref()).unwrap_or("")
+                        text
                     );
                 }
             }
         }
 
-        if is_last_node {
-            code_file.classes.push(class.clone());
-        }
+        JavaIdent::insert_lines(&mut code_file, tree.root_node(), code);
 
         code_file
     }
+
+    /// `import java.util.*;` is `java.util.*`, `import static org.junit.Assert.assertEquals;`
+    /// is `org.junit.Assert.assertEquals`
+    fn create_import(node: Node, code: &str) -> CodeImport {
+        let mut import = CodeImport {
+            source: code[node.byte_range()].to_string(),
+            line: node.start_position().row,
+            ..Default::default()
+        };
+
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            match child.kind() {
+                "static" => import.is_static = true,
+                "asterisk" => import.is_wildcard = true,
+                "identifier" | "scoped_identifier" => import.name = code[child.byte_range()].to_string(),
+                _ => {}
+            }
+        }
+
+        import.import = if import.is_wildcard {
+            format!("{}.*", import.name)
+        } else {
+            import.name.clone()
+        };
+        import
+    }
+
+    /// class, interface, enum, record or annotation type, then its nested types, which are
+    /// named by outer types, like `Outer.Inner`
+    fn insert_classes(classes: &mut Vec<CodeClass>, class_node: Node, outer: &str, package: &str, code: &str) {
+        let name_node = match class_node.child_by_field_name("name") {
+            Some(name) => name,
+            None => { return; }
+        };
+
+        let name = &code[name_node.byte_range()];
+        let kind = match class_node.kind() {
+            "interface_declaration" => ClassKind::Interface,
+            "enum_declaration" => ClassKind::Enum,
+            "record_declaration" => ClassKind::Record,
+            "annotation_type_declaration" => ClassKind::Annotation,
+            _ => ClassKind::Class,
+        };
+
+        let mut class = CodeClass {
+            name: if outer.is_empty() { name.to_string() } else { format!("{}.{}", outer, name) },
+            package: package.to_string(),
+            kind,
+            is_abstract: matches!(kind, ClassKind::Interface | ClassKind::Annotation)
+                || JavaIdent::has_modifier(class_node, code, "abstract"),
+            annotations: JavaIdent::annotations(class_node, code),
+            constant: JavaIdent::record_components(class_node, code),
+            ..Default::default()
+        };
+        JavaIdent::insert_location(&mut class, class_node);
+
+        let mut cursor = class_node.walk();
+        for child in class_node.children(&mut cursor) {
+            match child.kind() {
+                "superclass" | "extends_interfaces" => {
+                    class.extends.extend(JavaIdent::type_names(child, code));
+                }
+                "super_interfaces" => {
+                    class.implements.extend(JavaIdent::type_names(child, code));
+                }
+                _ => {}
+            }
+        }
+
+        let mut nested = vec![];
+        if let Some(body) = class_node.child_by_field_name("body") {
+            JavaIdent::insert_members(&mut class, name, body, code, &mut nested);
+        }
+
+        let outer = class.name.clone();
+        classes.push(class);
+        for nested_node in nested {
+            JavaIdent::insert_classes(classes, nested_node, &outer, package, code);
+        }
+    }
+
+    /// methods, fields and enum constants of class body, nested types are collected into `nested`
+    fn insert_members<'a>(class: &mut CodeClass, simple_name: &str, body: Node<'a>, code: &str, nested: &mut Vec<Node<'a>>) {
+        let mut cursor = body.walk();
+        for child in body.named_children(&mut cursor) {
+            match child.kind() {
+                "method_declaration" | "constructor_declaration" | "annotation_type_element_declaration" => {
+                    if let Some(name) = child.child_by_field_name("name") {
+                        let mut function = JavaIdent::create_function_by_node(child, &code[name.byte_range()]);
+                        function.annotations = JavaIdent::annotations(child, code);
+                        class.functions.push(function);
+                    }
+                }
+                "field_declaration" | "constant_declaration" => {
+                    let typ = child.child_by_field_name("type")
+                        .map(|typ| code[typ.byte_range()].to_string())
+                        .unwrap_or_default();
+                    let is_constant = child.kind() == "constant_declaration"
+                        || (JavaIdent::has_modifier(child, code, "static") && JavaIdent::has_modifier(child, code, "final"));
+
+                    let mut cursor = child.walk();
+                    for declarator in child.children_by_field_name("declarator", &mut cursor) {
+                        if let Some(name) = declarator.child_by_field_name("name") {
+                            class.constant.push(ClassConstant {
+                                name: code[name.byte_range()].to_string(),
+                                typ: typ.clone(),
+                                is_constant,
+                            });
+                        }
+                    }
+                }
+                "enum_constant" => {
+                    if let Some(name) = child.child_by_field_name("name") {
+                        class.constant.push(ClassConstant {
+                            name: code[name.byte_range()].to_string(),
+                            typ: simple_name.to_string(),
+                            is_constant: true,
+                        });
+                    }
+                }
+                "enum_body_declarations" => {
+                    JavaIdent::insert_members(class, simple_name, child, code, nested);
+                }
+                "class_declaration" | "interface_declaration" | "enum_declaration" | "record_declaration" | "annotation_type_declaration" => {
+                    nested.push(child);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// `Base` of `extends Base<T>`, `List` and `java.io.Serializable` of `implements List<T>, java.io.Serializable`
+    fn type_names(clause: Node, code: &str) -> Vec<String> {
+        let list = match clause.kind() {
+            "superclass" => Some(clause),
+            _ => {
+                let mut cursor = clause.walk();
+                let list = clause.named_children(&mut cursor).find(|child| child.kind() == "type_list");
+                list
+            }
+        };
+
+        let mut names = vec![];
+        if let Some(list) = list {
+            let mut cursor = list.walk();
+            for typ in list.named_children(&mut cursor) {
+                let text = &code[typ.byte_range()];
+                names.push(text.split('<').next().unwrap_or(text).trim().to_string());
+            }
+        }
+        names
+    }
+
+    /// `RequestMapping` of `@RequestMapping(value = "/ex")`, `ResponseBody` of `@ResponseBody`
+    fn annotations(node: Node, code: &str) -> Vec<CodeAnnotation> {
+        let mut annotations = vec![];
+        let mut cursor = node.walk();
+        for modifiers in node.children(&mut cursor).filter(|child| child.kind() == "modifiers") {
+            let mut cursor = modifiers.walk();
+            for annotation in modifiers.named_children(&mut cursor) {
+                if let Some(name) = annotation.child_by_field_name("name") {
+                    annotations.push(CodeAnnotation::new(&code[name.byte_range()]));
+                }
+            }
+        }
+        annotations
+    }
+
+    fn has_modifier(node: Node, code: &str, modifier: &str) -> bool {
+        let mut cursor = node.walk();
+        let has_modifier = node.children(&mut cursor)
+            .filter(|child| child.kind() == "modifiers")
+            .any(|modifiers| {
+                let mut cursor = modifiers.walk();
+                let found = modifiers.children(&mut cursor)
+                    .any(|child| &code[child.byte_range()] == modifier);
+                found
+            });
+        has_modifier
+    }
+
+    /// `@NotNull Integer x, List<Long> ids` of `record Point(...)` are kept as fields `x` and `ids`
+    fn record_components(class_node: Node, code: &str) -> Vec<ClassConstant> {
+        let mut components = vec![];
+        if let Some(parameters) = class_node.child_by_field_name("parameters") {
+            let mut cursor = parameters.walk();
+            for parameter in parameters.named_children(&mut cursor) {
+                if let (Some(typ), Some(name)) = (parameter.child_by_field_name("type"), parameter.child_by_field_name("name")) {
+                    components.push(ClassConstant {
+                        name: code[name.byte_range()].to_string(),
+                        typ: code[typ.byte_range()].to_string(),
+                        is_constant: false,
+                    });
+                }
+            }
+        }
+        components
+    }
 }
 
 impl CodeIdent for JavaIdent {
+    fn branch_kinds() -> &'static BranchKinds {
+        &JAVA_BRANCHES
+    }
+
     fn parse(code: &str) -> CodeFile {
         let mut ident = JavaIdent::new();
         JavaIdent::do_parse(&code, &mut ident)
@@ -124,6 +311,8 @@ impl CodeIdent for JavaIdent {
 
 #[cfg(test)]
 mod tests {
+    use guarding_core::domain::code_class::ClassKind;
+
     use crate::code_ident::CodeIdent;
     use crate::java_ident::JavaIdent;
 
@@ -137,6 +326,31 @@ import payroll.Employee;
         assert_eq!(3, file.imports.len());
     }
 
+    #[test]
+    fn should_parse_static_and_wildcard_import() {
+        let source_code = "package com.phodal;
+
+import java.util.*;
+import static org.junit.Assert.assertEquals;
+import static org.junit.Assert.*;
+";
+        let file = JavaIdent::parse(source_code);
+        assert_eq!(vec!["java.util.*", "org.junit.Assert.assertEquals", "org.junit.Assert.*"], file.imports);
+
+        let imports = &file.code_imports;
+        assert_eq!("java.util", imports[0].name);
+        assert!(imports[0].is_wildcard);
+        assert!(!imports[0].is_static);
+        assert_eq!(2, imports[0].line);
+
+        assert!(imports[1].is_static);
+        assert!(!imports[1].is_wildcard);
+        assert_eq!("import static org.junit.Assert.assertEquals;", imports[1].source);
+
+        assert_eq!("org.junit.Assert", imports[2].name);
+        assert!(imports[2].is_static && imports[2].is_wildcard);
+    }
+
     #[test]
     fn should_parse_impl_java_class() {
         let source_code = "class DateTimeImpl implements DateTime {
@@ -197,10 +411,193 @@ class DateTimeImpl2 {
 }";
 
         let file = JavaIdent::parse(source_code);
-        assert_eq!(1, file.classes.len());
+        assert_eq!(2, file.classes.len());
+        assert_eq!("OuterClass", file.classes[0].name);
+        assert_eq!("OuterClass.InnerClass", file.classes[1].name);
+        assert_eq!("y", file.classes[1].constant[0].name);
+        assert_eq!(1, file.classes[0].constant.len());
+    }
+
+    #[test]
+    fn should_parse_superclass_and_interfaces() {
+        let source_code = "package com.phodal.parser;
+
+public class JsonParser extends AbstractParser<Json> implements BaseParser<Json>, java.io.Serializable {
+}
+
+interface BaseParser<T> extends Parser, Closeable {
+    int MAX_DEPTH = 10;
+    T parse(String text);
+}
+";
+
+        let file = JavaIdent::parse(source_code);
+        let parser = &file.classes[0];
+        assert_eq!(ClassKind::Class, parser.kind);
+        assert_eq!("com.phodal.parser", parser.package);
+        assert_eq!(vec!["AbstractParser"], parser.extends);
+        assert_eq!(vec!["BaseParser", "java.io.Serializable"], parser.implements);
+
+        let base = &file.classes[1];
+        assert_eq!(ClassKind::Interface, base.kind);
+        assert_eq!(vec!["Parser", "Closeable"], base.extends);
+        assert!(base.constant[0].is_constant);
+        assert_eq!("parse", base.functions[0].name);
+    }
+
+    #[test]
+    fn should_parse_enum_and_annotation_type() {
+        let source_code = "public enum Status implements Labeled {
+    ACTIVE, INACTIVE;
+
+    private final String label = \"\";
+
+    public String label() {
+        return label;
+    }
+
+    enum Level { LOW }
+}
+
+@interface Audited {
+    String value() default \"\";
+}
+";
+
+        let file = JavaIdent::parse(source_code);
+        let names: Vec<&str> = file.classes.iter().map(|class| class.name.as_str()).collect();
+        assert_eq!(vec!["Status", "Status.Level", "Audited"], names);
+
+        let status = &file.classes[0];
+        assert_eq!(ClassKind::Enum, status.kind);
+        assert_eq!(vec!["Labeled"], status.implements);
+        let fields: Vec<&str> = status.constant.iter().map(|field| field.name.as_str()).collect();
+        assert_eq!(vec!["ACTIVE", "INACTIVE", "label"], fields);
+        assert_eq!("Status", status.constant[0].typ);
+        assert!(status.constant[0].is_constant);
+        assert_eq!("label", status.functions[0].name);
+
+        assert_eq!(ClassKind::Annotation, file.classes[2].kind);
+        assert!(file.classes[2].is_abstract);
+        assert_eq!("value", file.classes[2].functions[0].name);
+    }
+
+    #[test]
+    fn should_parse_record() {
+        let source_code = "// record Fake(int x) in comment
+public record Point(@NotNull Integer x, Map<String, Integer> tags) implements Shape {
+    static final Point ORIGIN = null;
+
+    public int sum() {
+        return x;
+    }
+}
+
+class Holder {
+    record Entry<K>(K key) {}
+}
+";
+
+        let file = JavaIdent::parse(source_code);
+        let names: Vec<&str> = file.classes.iter().map(|class| class.name.as_str()).collect();
+        assert_eq!(vec!["Point", "Holder", "Holder.Entry"], names);
+
+        let point = &file.classes[0];
+        assert_eq!(ClassKind::Record, point.kind);
+        assert_eq!(1, point.start.row);
+        assert_eq!(vec!["Shape"], point.implements);
+        let fields: Vec<(&str, &str)> = point.constant.iter().map(|field| (field.name.as_str(), field.typ.as_str())).collect();
+        assert_eq!(vec![("x", "Integer"), ("tags", "Map<String, Integer>"), ("ORIGIN", "Point")], fields);
+        assert_eq!("sum", point.functions[0].name);
+        assert_eq!(4, point.functions[0].start.row);
+
+        assert_eq!(ClassKind::Record, file.classes[2].kind);
+        assert_eq!("key", file.classes[2].constant[0].name);
+    }
+
+    #[test]
+    fn should_mark_interface_and_abstract_class() {
+        let source_code = "public interface BaseParser {
+}
+
+public abstract class AbstractParser implements BaseParser {
+}
+
+public class JsonParser extends AbstractParser {
+}
+";
+
+        let file = JavaIdent::parse(source_code);
+        assert_eq!(3, file.classes.len());
+        assert!(file.classes[0].is_abstract);
+        assert!(file.classes[1].is_abstract);
+        assert!(!file.classes[2].is_abstract);
+    }
+
+    #[test]
+    fn should_measure_method_complexity() {
+        let source_code = "class Checker {
+    public Checker() {
+    }
+
+    public int check(int a, boolean b) {
+        if (a > 0 && b) {
+            for (int i = 0; i < a; i++) {
+                if (i == 2) {
+                    return i;
+                } else if (i == 3) {
+                    return 0;
+                }
+            }
+        }
+
+        switch (a) {
+            case 1: return 1;
+            case 2: return 2;
+            default: return 3;
+        }
+    }
+}";
+
+        let file = JavaIdent::parse(source_code);
+        let functions = &file.classes[0].functions;
+        assert_eq!(2, functions.len());
+
+        assert_eq!("Checker", functions[0].name);
+        assert_eq!(1, functions[0].complexity);
+        assert_eq!(0, functions[0].nesting);
+
+        assert_eq!("check", functions[1].name);
+        assert_eq!(8, functions[1].complexity);
+        assert_eq!(3, functions[1].nesting);
+    }
+
+    #[test]
+    fn should_count_fields_and_lines() {
+        let source_code = "// header
+class Point {
+    /* position */
+    private int x, y;
+    private static final String NAME = \"point\";
+
+    public int getX() {
+        return x;
+    }
+}
+";
+
+        let file = JavaIdent::parse(source_code);
+        assert_eq!(10, file.lines);
+        assert_eq!(7, file.loc);
+
+        let class = &file.classes[0];
+        let fields: Vec<&str> = class.constant.iter().map(|field| field.name.as_str()).collect();
+        assert_eq!(vec!["x", "y", "NAME"], fields);
+        assert_eq!("String", class.constant[2].typ);
+        assert!(!class.constant[0].is_constant);
+        assert!(class.constant[2].is_constant);
     }
 
-    #[ignore]
     #[test]
     fn should_support_annotation() {
         let source_code = "public class HelloController {
@@ -212,6 +609,9 @@ class DateTimeImpl2 {
 }";
 
         let file = JavaIdent::parse(source_code);
-        println!("{:?}", file);
+        let annotations: Vec<&str> = file.classes[0].functions[0].annotations.iter()
+            .map(|annotation| annotation.name.as_str())
+            .collect();
+        assert_eq!(vec!["RequestMapping", "ResponseBody"], annotations);
     }
 }
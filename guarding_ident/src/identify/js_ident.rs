@@ -3,6 +3,7 @@ use tree_sitter::{Node, Parser, Query, QueryCursor};
 use guarding_core::domain::code_file::CodeFile;
 use guarding_core::domain::code_class::CodeClass;
 use crate::code_ident::CodeIdent;
+use crate::identify::complexity::BranchKinds;
 
 const JS_QUERY: &'static str = "
 (import_specifier
@@ -12,6 +13,17 @@ const JS_QUERY: &'static str = "
 	source: (string) @source)
 (import_clause (identifier) @import-name)
 
+(export_statement
+	source: (string) @source)
+
+(call_expression
+	function: (identifier) @require
+	arguments: (arguments . (string) @source)
+	(#eq? @require \"require\"))
+(call_expression
+	function: (import)
+	arguments: (arguments . (string) @source))
+
 (class_declaration
   name: (identifier) @class-name
   body: (class_body
@@ -23,9 +35,15 @@ const JS_QUERY: &'static str = "
 )
 
 (program (function_declaration
-      name: * @function-name))
+      name: (_) @function-name))
 ";
 
+const JS_BRANCHES: BranchKinds = BranchKinds {
+    decisions: &["if_statement", "for_statement", "for_in_statement", "while_statement", "do_statement", "catch_clause", "ternary_expression", "switch_case"],
+    nestings: &["if_statement", "for_statement", "for_in_statement", "while_statement", "do_statement", "switch_statement", "try_statement"],
+    boundaries: &["function_declaration", "generator_function_declaration", "function", "arrow_function", "class_declaration", "method_definition"],
+};
+
 pub struct JsIdent {
     parser: Parser,
     query: Query,
@@ -47,7 +65,7 @@ impl JsIdent {
 
 impl JsIdent {
     fn do_parse(code: &str, ident: &mut JsIdent) -> CodeFile {
-        let text_callback = |n: Node| &code[n.byte_range()];
+        let text_callback = |n: Node| std::iter::once(code[n.byte_range()].as_bytes());
         let tree = ident.parser.parse(code, None).unwrap();
 
         let mut query_cursor = QueryCursor::new();
@@ -64,6 +82,7 @@ impl JsIdent {
             let text = capture.node.utf8_text((&code).as_ref()).unwrap_or("");
             match capture_name.as_str() {
                 "source" => {
+                    // `import`, `export ... from`, `require('../db')` or `import('./x')`
                     code_file.imports.push(text.to_string());
                 }
                 "class-name" => {
@@ -79,6 +98,7 @@ impl JsIdent {
                     code_file.functions.push(JsIdent::create_function(capture, text));
                 }
                 "import-name" => {}
+                "require" => {}
                 "parameter" => {}
                 &_ => {
                     println!(
@@ -99,11 +119,17 @@ impl JsIdent {
             }
         }
 
+        JsIdent::insert_lines(&mut code_file, tree.root_node(), code);
+
         code_file
     }
 }
 
 impl CodeIdent for JsIdent {
+    fn branch_kinds() -> &'static BranchKinds {
+        &JS_BRANCHES
+    }
+
     fn parse(code: &str) -> CodeFile {
         let mut ident = JsIdent::new();
         JsIdent::do_parse(code, &mut ident)
@@ -143,6 +169,23 @@ function abc() {
         assert_eq!("abc", funcs.name);
     }
 
+    #[test]
+    fn should_parse_require_dynamic_import_and_reexport() {
+        let source_code = "const db = require('../db');
+const { Router } = require(\"express\");
+export { User } from './user.js';
+export * from './order';
+
+async function load(name) {
+  const lazy = await import('./lazy.js');
+  return require(name);
+}
+";
+        let file = JsIdent::parse(source_code);
+
+        assert_eq!(vec!["'../db'", "\"express\"", "'./user.js'", "'./order'", "'./lazy.js'"], file.imports);
+    }
+
     #[test]
     fn should_parse_func_location() {
         let source_code = "function abc() {
@@ -159,4 +202,34 @@ function abc() {
         assert_eq!(2, funcs.end.row);
         assert_eq!(1, funcs.end.column);
     }
+
+    #[test]
+    fn should_measure_function_complexity() {
+        let source_code = "function check(a, b) {
+  try {
+    while (a > 0 || b) {
+      a = a > 10 ? a - 2 : a - 1;
+    }
+  } catch (e) {
+    return -1;
+  }
+
+  if (a) {
+    return 1;
+  } else if (b) {
+    return 2;
+  }
+
+  const inner = function () {
+    if (a) {}
+  };
+  return 0;
+}
+";
+        let file = JsIdent::parse(source_code);
+        let funcs = &file.functions[0];
+
+        assert_eq!(7, funcs.complexity);
+        assert_eq!(2, funcs.nesting);
+    }
 }
@@ -0,0 +1,314 @@
+use tree_sitter::{Node, Parser, Query, QueryCursor};
+
+use guarding_core::domain::code_annotation::CodeAnnotation;
+use guarding_core::domain::code_class::{ClassConstant, CodeClass};
+use guarding_core::domain::code_file::CodeFile;
+use guarding_core::domain::code_function::CodeFunction;
+use crate::code_ident::CodeIdent;
+use crate::identify::complexity::BranchKinds;
+
+const KOTLIN_QUERY: &str = "
+(package_header
+	(identifier) @package-name)
+
+(import_header
+	(identifier) @import-name)
+
+(source_file (class_declaration) @class)
+(source_file (object_declaration) @class)
+(source_file (function_declaration) @function)
+";
+
+const KOTLIN_BRANCHES: BranchKinds = BranchKinds {
+    decisions: &["if_expression", "for_statement", "while_statement", "do_while_statement", "catch_block", "when_entry", "conjunction_expression", "disjunction_expression"],
+    nestings: &["if_expression", "for_statement", "while_statement", "do_while_statement", "when_expression", "try_expression"],
+    boundaries: &["function_declaration", "class_declaration", "object_declaration", "lambda_literal", "anonymous_function"],
+};
+
+/// Kotlin ident for `.kt` and `.kts`, the package and imports have the same dotted form as Java
+pub struct KotlinIdent {
+    parser: Parser,
+    query: Query,
+}
+
+impl KotlinIdent {
+    fn new() -> KotlinIdent {
+        let mut parser = Parser::new();
+
+        let language = tree_sitter_kotlin::language();
+        parser.set_language(language).unwrap();
+
+        let query = Query::new(language, KOTLIN_QUERY)
+            .map_err(|e| println!("Query compilation failed: {:?}", e)).unwrap();
+        KotlinIdent { parser, query }
+    }
+}
+
+impl KotlinIdent {
+    fn do_parse(code: &str, ident: &mut KotlinIdent) -> CodeFile {
+        let text_callback = |n: Node| std::iter::once(code[n.byte_range()].as_bytes());
+        let tree = ident.parser.parse(code, None).unwrap();
+
+        let mut query_cursor = QueryCursor::new();
+        let captures = query_cursor.captures(&ident.query, tree.root_node(), text_callback);
+
+        let mut code_file = CodeFile::default();
+
+        for (mat, capture_index) in captures {
+            let capture = mat.captures[capture_index];
+            let capture_name = &ident.query.capture_names()[capture.index as usize];
+
+            let text = capture.node.utf8_text((&code).as_ref()).unwrap_or("");
+            match capture_name.as_str() {
+                "package-name" => {
+                    code_file.package = text.to_string();
+                }
+                "import-name" => {
+                    code_file.imports.push(text.to_string());
+                }
+                "class" => {
+                    let mut class = KotlinIdent::create_class(capture.node, code);
+                    class.package = code_file.package.clone();
+                    code_file.classes.push(class);
+                }
+                "function" => {
+                    code_file.functions.extend(KotlinIdent::create_kotlin_function(capture.node, code));
+                }
+                &_ => {}
+            }
+        }
+
+        KotlinIdent::insert_lines(&mut code_file, tree.root_node(), code);
+
+        code_file
+    }
+
+    /// class, data class, interface or object. a supertype with constructor call, like
+    /// `Base()`, is the superclass, others are interfaces. supertypes of interface are `extends`
+    fn create_class(class_node: Node, code: &str) -> CodeClass {
+        let mut class = CodeClass::default();
+        KotlinIdent::insert_location(&mut class, class_node);
+
+        let is_interface = KotlinIdent::has_child(class_node, code, "interface");
+
+        let mut cursor = class_node.walk();
+        for child in class_node.children(&mut cursor) {
+            match child.kind() {
+                "type_identifier" if class.name.is_empty() => {
+                    class.name = code[child.byte_range()].to_string();
+                }
+                "modifiers" => {
+                    class.annotations = KotlinIdent::annotations(child, code);
+                    class.is_abstract = class.is_abstract || KotlinIdent::has_child(child, code, "abstract");
+                }
+                "delegation_specifier" => {
+                    let (name, is_constructor) = KotlinIdent::supertype(child, code);
+                    if is_constructor || is_interface {
+                        class.extends.push(name);
+                    } else {
+                        class.implements.push(name);
+                    }
+                }
+                "class_body" | "enum_class_body" => {
+                    KotlinIdent::insert_members(&mut class, child, code);
+                }
+                _ => {}
+            }
+        }
+
+        class.is_abstract = class.is_abstract || is_interface;
+        class
+    }
+
+    /// functions and properties, functions of companion object are included
+    fn insert_members(class: &mut CodeClass, body: Node, code: &str) {
+        let mut cursor = body.walk();
+        for child in body.named_children(&mut cursor) {
+            match child.kind() {
+                "function_declaration" => {
+                    class.functions.extend(KotlinIdent::create_kotlin_function(child, code));
+                }
+                "property_declaration" => {
+                    class.constant.extend(KotlinIdent::create_property(child, code));
+                }
+                "companion_object" => {
+                    let mut cursor = child.walk();
+                    let companion_body = child.named_children(&mut cursor).find(|node| node.kind() == "class_body");
+                    if let Some(companion_body) = companion_body {
+                        KotlinIdent::insert_members(class, companion_body, code);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn create_kotlin_function(function_node: Node, code: &str) -> Option<CodeFunction> {
+        let mut cursor = function_node.walk();
+        let name = function_node.children(&mut cursor).find(|child| child.kind() == "simple_identifier")?;
+
+        let mut function = KotlinIdent::create_function_by_node(function_node, &code[name.byte_range()]);
+        let mut cursor = function_node.walk();
+        if let Some(modifiers) = function_node.children(&mut cursor).find(|child| child.kind() == "modifiers") {
+            function.annotations = KotlinIdent::annotations(modifiers, code);
+        }
+
+        Some(function)
+    }
+
+    /// `val name: String`, `const val` is constant
+    fn create_property(property_node: Node, code: &str) -> Option<ClassConstant> {
+        let mut cursor = property_node.walk();
+        let declaration = property_node.children(&mut cursor).find(|child| child.kind() == "variable_declaration")?;
+
+        let mut cursor = declaration.walk();
+        let name = declaration.children(&mut cursor).find(|child| child.kind() == "simple_identifier")?;
+        let mut cursor = declaration.walk();
+        let typ = declaration.named_children(&mut cursor)
+            .find(|child| child.kind() != "simple_identifier")
+            .map(|typ| code[typ.byte_range()].to_string())
+            .unwrap_or_default();
+
+        let mut cursor = property_node.walk();
+        let is_constant = property_node.children(&mut cursor)
+            .filter(|child| child.kind() == "modifiers")
+            .any(|modifiers| KotlinIdent::has_child(modifiers, code, "const"));
+
+        Some(ClassConstant { name: code[name.byte_range()].to_string(), typ, is_constant })
+    }
+
+    /// name of supertype, and whether it's a constructor invocation
+    fn supertype(specifier: Node, code: &str) -> (String, bool) {
+        let typ = specifier.named_child(0).unwrap_or(specifier);
+        let is_constructor = typ.kind() == "constructor_invocation";
+        let user_type = if is_constructor { typ.named_child(0).unwrap_or(typ) } else { typ };
+
+        let text = &code[user_type.byte_range()];
+        (text.split('<').next().unwrap_or(text).trim().to_string(), is_constructor)
+    }
+
+    /// `Service` of `@Service`, `RequestMapping` of `@RequestMapping("/users")`
+    fn annotations(modifiers: Node, code: &str) -> Vec<CodeAnnotation> {
+        let mut cursor = modifiers.walk();
+        let annotations = modifiers.named_children(&mut cursor)
+            .filter(|child| child.kind() == "annotation")
+            .filter_map(|annotation| annotation.named_child(0))
+            .map(|typ| {
+                let user_type = match typ.kind() {
+                    "constructor_invocation" => typ.named_child(0).unwrap_or(typ),
+                    _ => typ
+                };
+                CodeAnnotation::new(&code[user_type.byte_range()])
+            })
+            .collect();
+        annotations
+    }
+
+    /// keyword or modifier of `node`, directly or in a modifier node like `class_modifier`
+    fn has_child(node: Node, code: &str, keyword: &str) -> bool {
+        let mut cursor = node.walk();
+        let has_child = node.children(&mut cursor)
+            .any(|child| {
+                let text = &code[child.byte_range()];
+                text == keyword && (!child.is_named() || child.kind().ends_with("_modifier"))
+            });
+        has_child
+    }
+}
+
+impl CodeIdent for KotlinIdent {
+    fn branch_kinds() -> &'static BranchKinds {
+        &KOTLIN_BRANCHES
+    }
+
+    fn parse(code: &str) -> CodeFile {
+        let mut ident = KotlinIdent::new();
+        KotlinIdent::do_parse(code, &mut ident)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::code_ident::CodeIdent;
+    use crate::kotlin_ident::KotlinIdent;
+
+    #[test]
+    fn should_parse_package_and_imports() {
+        let source_code = "package com.phodal.app.service
+
+import com.phodal.app.domain.User
+import org.springframework.stereotype.Service as Svc
+";
+        let file = KotlinIdent::parse(source_code);
+
+        assert_eq!("com.phodal.app.service", file.package);
+        assert_eq!(vec!["com.phodal.app.domain.User", "org.springframework.stereotype.Service"], file.imports);
+    }
+
+    #[test]
+    fn should_parse_class_with_supertypes() {
+        let source_code = "package com.phodal.app
+
+@Service
+abstract class UserService(private val repo: Repository) : BaseService(), Closeable, Repository<User> {
+    val name: String = \"\"
+
+    @Transactional
+    fun find(id: Long): User? {
+        return null
+    }
+
+    companion object {
+        const val MAX_SIZE = 20
+        fun create(): UserService = TODO()
+    }
+}
+";
+        let file = KotlinIdent::parse(source_code);
+
+        let class = &file.classes[0];
+        assert_eq!("UserService", class.name);
+        assert_eq!("com.phodal.app", class.package);
+        assert!(class.is_abstract);
+        assert_eq!(vec!["BaseService"], class.extends);
+        assert_eq!(vec!["Closeable", "Repository"], class.implements);
+        assert_eq!("Service", class.annotations[0].name);
+
+        let functions: Vec<&str> = class.functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(vec!["find", "create"], functions);
+        assert_eq!("Transactional", class.functions[0].annotations[0].name);
+
+        assert_eq!(2, class.constant.len());
+        assert!(class.constant[1].is_constant);
+    }
+
+    #[test]
+    fn should_parse_interface_object_and_functions() {
+        let source_code = "interface Repository<T> : Closeable {
+    fun find(id: Long): T
+}
+
+data class User(val id: Long)
+
+object Registry : Repository<User>
+
+fun main() {
+    when (1) {
+        1 -> println(\"one\")
+        else -> println(\"other\")
+    }
+}
+";
+        let file = KotlinIdent::parse(source_code);
+
+        assert_eq!(3, file.classes.len());
+        assert!(file.classes[0].is_abstract);
+        assert_eq!(vec!["Closeable"], file.classes[0].extends);
+        assert_eq!("User", file.classes[1].name);
+        assert_eq!("Registry", file.classes[2].name);
+        assert_eq!(vec!["Repository"], file.classes[2].implements);
+
+        assert_eq!("main", file.functions[0].name);
+        assert_eq!(2, file.functions[0].complexity);
+    }
+}
@@ -1,5 +1,11 @@
 pub mod code_ident;
+pub mod complexity;
 pub mod js_ident;
+pub mod ts_ident;
 pub mod java_ident;
+pub mod kotlin_ident;
 pub mod rust_ident;
+pub mod go_ident;
+pub mod cpp_ident;
+pub mod python_ident;
 pub mod c_sharp_ident;
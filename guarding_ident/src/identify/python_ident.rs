@@ -0,0 +1,334 @@
+use tree_sitter::{Node, Parser, Query, QueryCursor};
+
+use guarding_core::domain::code_annotation::CodeAnnotation;
+use guarding_core::domain::code_class::{ClassConstant, CodeClass};
+use guarding_core::domain::code_file::CodeFile;
+use guarding_core::domain::code_function::CodeFunction;
+use guarding_parser::ast::NamingStyle;
+use crate::code_ident::CodeIdent;
+use crate::identify::complexity::BranchKinds;
+
+const PYTHON_QUERY: &str = "
+(import_statement) @import
+(import_from_statement) @import-from
+
+(module
+    [
+        (class_definition) @class
+        (decorated_definition definition: (class_definition) @class)
+    ]
+)
+
+(module
+    [
+        (function_definition) @function
+        (decorated_definition definition: (function_definition) @function)
+    ]
+)
+";
+
+const PYTHON_BRANCHES: BranchKinds = BranchKinds {
+    decisions: &["if_statement", "elif_clause", "for_statement", "while_statement", "except_clause", "conditional_expression", "boolean_operator", "for_in_clause", "if_clause"],
+    nestings: &["if_statement", "for_statement", "while_statement", "try_statement", "with_statement"],
+    boundaries: &["function_definition", "class_definition", "lambda"],
+};
+
+/// Python ident, the package is decided by `__init__.py` of directories, see `ImportResolver`
+pub struct PythonIdent {
+    parser: Parser,
+    query: Query,
+}
+
+impl PythonIdent {
+    fn new() -> PythonIdent {
+        let mut parser = Parser::new();
+
+        let language = tree_sitter_python::language();
+        parser.set_language(language).unwrap();
+
+        let query = Query::new(language, PYTHON_QUERY)
+            .map_err(|e| println!("Query compilation failed: {:?}", e)).unwrap();
+        PythonIdent { parser, query }
+    }
+}
+
+impl PythonIdent {
+    fn do_parse(code: &str, ident: &mut PythonIdent) -> CodeFile {
+        let text_callback = |n: Node| std::iter::once(code[n.byte_range()].as_bytes());
+        let tree = ident.parser.parse(code, None).unwrap();
+
+        let mut query_cursor = QueryCursor::new();
+        let captures = query_cursor.captures(&ident.query, tree.root_node(), text_callback);
+
+        let mut code_file = CodeFile::default();
+
+        for (mat, capture_index) in captures {
+            let capture = mat.captures[capture_index];
+            let capture_name = &ident.query.capture_names()[capture.index as usize];
+
+            match capture_name.as_str() {
+                "import" => {
+                    let mut cursor = capture.node.walk();
+                    for name in capture.node.children_by_field_name("name", &mut cursor) {
+                        code_file.imports.push(PythonIdent::imported_name(name, code).to_string());
+                    }
+                }
+                "import-from" => {
+                    code_file.imports.extend(PythonIdent::from_imports(capture.node, code));
+                }
+                "class" => {
+                    code_file.classes.push(PythonIdent::create_class(capture.node, code));
+                }
+                "function" => {
+                    if let Some(function) = PythonIdent::create_python_function(capture.node, code) {
+                        code_file.functions.push(function);
+                    }
+                }
+                &_ => {}
+            }
+        }
+
+        PythonIdent::insert_lines(&mut code_file, tree.root_node(), code);
+
+        code_file
+    }
+
+    /// `a.b` of `a.b` or `a.b as c`
+    fn imported_name<'a>(node: Node, code: &'a str) -> &'a str {
+        let name = match node.kind() {
+            "aliased_import" => node.child_by_field_name("name").unwrap_or(node),
+            _ => node
+        };
+
+        &code[name.byte_range()]
+    }
+
+    /// `from a.b import c, d` is `a.b.c` and `a.b.d`, `from a.b import *` is `a.b`. relative
+    /// imports keep their dots, like `..domain.User`, they are resolved by `ImportResolver`
+    fn from_imports(node: Node, code: &str) -> Vec<String> {
+        let module = match node.child_by_field_name("module_name") {
+            Some(module) => &code[module.byte_range()],
+            None => { return vec![]; }
+        };
+
+        let mut cursor = node.walk();
+        let names: Vec<&str> = node.children_by_field_name("name", &mut cursor)
+            .map(|name| PythonIdent::imported_name(name, code))
+            .collect();
+
+        if names.is_empty() {
+            return vec![module.to_string()];
+        }
+
+        names.iter()
+            .map(|name| if module.ends_with('.') {
+                format!("{}{}", module, name)
+            } else {
+                format!("{}.{}", module, name)
+            })
+            .collect()
+    }
+
+    fn create_class(class_node: Node, code: &str) -> CodeClass {
+        let name = class_node.child_by_field_name("name")
+            .map(|name| &code[name.byte_range()])
+            .unwrap_or("");
+
+        let mut class = CodeClass {
+            name: name.to_string(),
+            annotations: PythonIdent::decorators(class_node, code),
+            ..Default::default()
+        };
+        PythonIdent::insert_location(&mut class, PythonIdent::definition_node(class_node));
+
+        if let Some(superclasses) = class_node.child_by_field_name("superclasses") {
+            let mut cursor = superclasses.walk();
+            for base in superclasses.named_children(&mut cursor) {
+                // skip `metaclass=ABCMeta`
+                if base.kind() == "identifier" || base.kind() == "attribute" {
+                    let base_name = &code[base.byte_range()];
+                    class.is_abstract = class.is_abstract || base_name == "ABC" || base_name.ends_with(".ABC");
+                    class.extends.push(base_name.to_string());
+                }
+            }
+        }
+
+        if let Some(body) = class_node.child_by_field_name("body") {
+            PythonIdent::insert_members(&mut class, body, code);
+        }
+
+        class
+    }
+
+    /// methods and class attributes, names in `SCREAMING_SNAKE` are constants
+    fn insert_members(class: &mut CodeClass, body: Node, code: &str) {
+        let mut cursor = body.walk();
+        for child in body.named_children(&mut cursor) {
+            match child.kind() {
+                "function_definition" => {
+                    class.functions.extend(PythonIdent::create_python_function(child, code));
+                }
+                "decorated_definition" => {
+                    let definition = child.child_by_field_name("definition");
+                    if let Some(function) = definition.filter(|node| node.kind() == "function_definition") {
+                        class.functions.extend(PythonIdent::create_python_function(function, code));
+                    }
+                }
+                "expression_statement" => {
+                    let assignment = child.named_child(0).filter(|node| node.kind() == "assignment");
+                    let left = assignment.and_then(|node| node.child_by_field_name("left"));
+                    if let (Some(assignment), Some(left)) = (assignment, left.filter(|node| node.kind() == "identifier")) {
+                        let name = code[left.byte_range()].to_string();
+                        let typ = assignment.child_by_field_name("type")
+                            .map(|typ| code[typ.byte_range()].to_string())
+                            .unwrap_or_default();
+                        let is_constant = NamingStyle::ScreamingSnake.is_match(&name);
+
+                        class.constant.push(ClassConstant { name, typ, is_constant });
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn create_python_function(function_node: Node, code: &str) -> Option<CodeFunction> {
+        let name = function_node.child_by_field_name("name")?;
+
+        let mut function = PythonIdent::create_function_by_node(function_node, &code[name.byte_range()]);
+        function.annotations = PythonIdent::decorators(function_node, code);
+        let definition = PythonIdent::definition_node(function_node);
+        PythonIdent::insert_location(&mut function, definition);
+
+        Some(function)
+    }
+
+    /// the `decorated_definition` of a decorated class or function, so that the location
+    /// contains decorators
+    fn definition_node(node: Node) -> Node {
+        match node.parent() {
+            Some(parent) if parent.kind() == "decorated_definition" => parent,
+            _ => node
+        }
+    }
+
+    /// `app.get` of `@app.get("/")`, `dataclass` of `@dataclass`
+    fn decorators(node: Node, code: &str) -> Vec<CodeAnnotation> {
+        let decorated = PythonIdent::definition_node(node);
+        if decorated.kind() != "decorated_definition" {
+            return vec![];
+        }
+
+        let mut cursor = decorated.walk();
+        let decorators = decorated.named_children(&mut cursor)
+            .filter(|child| child.kind() == "decorator")
+            .filter_map(|decorator| decorator.named_child(0))
+            .map(|expr| {
+                let name = match expr.kind() {
+                    "call" => expr.child_by_field_name("function").unwrap_or(expr),
+                    _ => expr
+                };
+                CodeAnnotation::new(&code[name.byte_range()])
+            })
+            .collect();
+        decorators
+    }
+}
+
+impl CodeIdent for PythonIdent {
+    fn branch_kinds() -> &'static BranchKinds {
+        &PYTHON_BRANCHES
+    }
+
+    fn parse(code: &str) -> CodeFile {
+        let mut ident = PythonIdent::new();
+        PythonIdent::do_parse(code, &mut ident)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::code_ident::CodeIdent;
+    use crate::python_ident::PythonIdent;
+
+    #[test]
+    fn should_parse_imports() {
+        let source_code = "import os
+import numpy as np, app.domain.models
+from django.db import models, transaction as tx
+from . import views
+from ..domain.user import User
+from .serializers import *
+";
+        let file = PythonIdent::parse(source_code);
+
+        assert_eq!(vec![
+            "os", "numpy", "app.domain.models", "django.db.models", "django.db.transaction",
+            ".views", "..domain.user.User", ".serializers"
+        ], file.imports);
+    }
+
+    #[test]
+    fn should_parse_class_with_bases_and_decorators() {
+        let source_code = "@dataclass(frozen=True)
+class UserService(BaseService, abc.ABC, metaclass=Meta):
+    MAX_SIZE = 20
+    name: str = ''
+
+    def __init__(self, repo):
+        self.repo = repo
+
+    @staticmethod
+    @app.get('/users')
+    def find(id):
+        if id and id > 0:
+            return None
+        return [u for u in users if u]
+
+class Other:
+    pass
+";
+        let file = PythonIdent::parse(source_code);
+        assert_eq!(2, file.classes.len());
+
+        let class = &file.classes[0];
+        assert_eq!("UserService", class.name);
+        assert_eq!(0, class.start.row);
+        assert_eq!(vec!["BaseService", "abc.ABC"], class.extends);
+        assert!(class.is_abstract);
+        assert_eq!("dataclass", class.annotations[0].name);
+
+        assert_eq!(2, class.constant.len());
+        assert!(class.constant[0].is_constant);
+        assert_eq!("str", class.constant[1].typ);
+
+        assert_eq!(2, class.functions.len());
+        let find = &class.functions[1];
+        assert_eq!("find", find.name);
+        assert_eq!(8, find.start.row);
+        let annotations: Vec<&str> = find.annotations.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(vec!["staticmethod", "app.get"], annotations);
+        assert_eq!(5, find.complexity);
+        assert_eq!(1, find.nesting);
+
+        assert!(!file.classes[1].is_abstract);
+    }
+
+    #[test]
+    fn should_parse_module_functions() {
+        let source_code = "def main():
+    def inner():
+        pass
+
+@router.post('/')
+async def create(item):
+    pass
+";
+        let file = PythonIdent::parse(source_code);
+
+        let names: Vec<&str> = file.functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(vec!["main", "create"], names);
+        assert_eq!("router.post", file.functions[1].annotations[0].name);
+        assert_eq!(4, file.functions[1].start.row);
+    }
+}
@@ -4,34 +4,25 @@ use tree_sitter::{Node, Parser, Query, QueryCursor};
 
 use guarding_core::domain::code_file::CodeFile;
 use guarding_core::domain::code_function::CodeFunction;
-use guarding_core::domain::code_class::CodeClass;
+use guarding_core::domain::code_import::CodeImport;
+use guarding_core::domain::code_class::{ClassConstant, ClassKind, CodeClass};
+use guarding_parser::support::package_unify::PackageUnify;
 use crate::code_ident::CodeIdent;
+use crate::identify::complexity::BranchKinds;
 
-const RUST_QUERY: &'static str = "
-(use_declaration
-	(scoped_identifier) @import-name)
-
-(struct_item
-	name: (type_identifier) @struct-name
-    body: (field_declaration_list
-    	(field_declaration
-			name: (field_identifier) @field-name
-            type: (type_identifier) @type-name
-    ))?
-)
-
-(impl_item
-    trait: (type_identifier)? @trait-name
-	type: (type_identifier) @impl-struct-name
-    body: (declaration_list (
-	    (function_item
-        	name: (identifier) @impl-function-name
-        ))
-    )
-)
+const RUST_QUERY: &str = "
+(use_declaration) @use
 ";
 
+const RUST_BRANCHES: BranchKinds = BranchKinds {
+    decisions: &["if_expression", "if_let_expression", "while_expression", "while_let_expression", "for_expression", "match_arm"],
+    nestings: &["if_expression", "if_let_expression", "while_expression", "while_let_expression", "loop_expression", "for_expression", "match_expression"],
+    boundaries: &["function_item", "closure_expression", "impl_item", "trait_item", "mod_item"],
+};
 
+/// Rust ident, structs, enums and traits are classes. the package of file is its module path,
+/// which is set by `ModelBuilder`; classes in inline modules, like `mod tests {}`, keep the
+/// inline module path in `package`, like `tests`
 pub struct RustIdent {
     parser: Parser,
     query: Query
@@ -44,89 +35,341 @@ impl RustIdent {
         let language = tree_sitter_rust::language();
         parser.set_language(language).unwrap();
 
-        let query = Query::new(language, &RUST_QUERY)
-            .map_err(|e| println!("{}", format!("Query compilation failed: {:?}", e))).unwrap();
+        let query = Query::new(language, RUST_QUERY)
+            .map_err(|e| println!("Query compilation failed: {:?}", e)).unwrap();
         RustIdent { parser, query }
     }
 }
 
+/// functions of `impl` blocks by type name, and traits which the type implements
+#[derive(Default)]
+struct Impls {
+    functions: HashMap<String, Vec<CodeFunction>>,
+    traits: HashMap<String, Vec<String>>,
+    /// type names in source order
+    types: Vec<String>,
+}
+
 impl RustIdent {
     fn do_parse(code: &str, ident: &mut RustIdent) -> CodeFile {
-        let text_callback = |n: Node| &code[n.byte_range()];
+        let text_callback = |n: Node| std::iter::once(code[n.byte_range()].as_bytes());
         let tree = ident.parser.parse(code, None).unwrap();
 
         let mut query_cursor = QueryCursor::new();
         let captures = query_cursor.captures(&ident.query, tree.root_node(), text_callback);
 
         let mut code_file = CodeFile::default();
-        let mut last_class_end_line = 0;
-        let mut class = CodeClass::default();
-
-        let mut last_impl_struct_name = "".to_string();
-        let mut last_trait_name = "".to_string();
-        let mut impl_functions: HashMap<String, Vec<CodeFunction>> = Default::default();
-        let mut trait_struct_map: HashMap<String, String> = Default::default();
 
         for (mat, capture_index) in captures {
             let capture = mat.captures[capture_index];
             let capture_name = &ident.query.capture_names()[capture.index as usize];
 
-            let text = capture.node.utf8_text((&code).as_ref()).unwrap_or("");
-            match capture_name.as_str() {
-                "import-name" => {
-                    code_file.imports.push(text.to_string());
-                },
-                "struct-name" => {
-                    class.name = text.to_string();
-                    let struct_node = capture.node;
-                    last_class_end_line = struct_node.end_position().row;
-                    RustIdent::insert_location(&mut class, struct_node);
-                },
-                "impl-struct-name" => {
-                    last_impl_struct_name = text.to_string();
-                    if last_trait_name != "" {
-                        trait_struct_map.insert(text.to_string(), last_trait_name);
-                    }
-                    last_trait_name = "".to_string();
+            if capture_name.as_str() == "use" {
+                for import in RustIdent::use_imports(capture.node, code) {
+                    code_file.imports.push(import.import.clone());
+                    code_file.code_imports.push(import);
                 }
-                "impl-function-name" => {
-                    let function = RustIdent::create_function(capture, text);
-                    impl_functions
-                        .entry(last_impl_struct_name.clone())
-                        .or_insert_with(Vec::new)
-                        .push(function);
+            }
+        }
+
+        let mut impls = Impls::default();
+        RustIdent::insert_items(&mut code_file, tree.root_node(), &[], code, &mut impls);
+
+        for clz in code_file.classes.iter_mut() {
+            if let Some(functions) = impls.functions.remove(clz.name.as_str()) {
+                clz.functions.extend(functions);
+            }
+
+            if let Some(traits) = impls.traits.get(clz.name.as_str()) {
+                clz.implements.extend(traits.iter().cloned());
+            }
+        }
+
+        // methods of types which are declared in other files
+        for type_name in impls.types {
+            if let Some(functions) = impls.functions.remove(&type_name) {
+                code_file.functions.extend(functions);
+            }
+        }
+
+        RustIdent::insert_lines(&mut code_file, tree.root_node(), code);
+
+        code_file
+    }
+
+    /// every leaf of use tree is an import: `use a::{b, c::{self, d as e}, f::*}` is `a::b`,
+    /// `a::c`, `a::c::d` with alias `e`, and glob `a::f::*`
+    fn use_imports(use_node: Node, code: &str) -> Vec<CodeImport> {
+        let mut cursor = use_node.walk();
+        let is_reexport = use_node.children(&mut cursor).any(|child| child.kind() == "visibility_modifier");
+        let source = code[use_node.byte_range()].to_string();
+        let module = RustIdent::inline_module(use_node, code);
+
+        let mut leaves: Vec<(String, Option<String>)> = vec![];
+        if let Some(argument) = use_node.child_by_field_name("argument") {
+            RustIdent::use_leaves(argument, "", code, &mut leaves);
+        }
+
+        leaves.into_iter()
+            .map(|(path, alias)| {
+                let is_wildcard = path.ends_with('*');
+                let name = path.trim_end_matches('*').trim_end_matches("::");
+                CodeImport {
+                    name: PackageUnify::from_rust_import(name, false),
+                    import: path.clone(),
+                    source: source.clone(),
+                    is_wildcard,
+                    alias,
+                    is_reexport,
+                    line: use_node.start_position().row,
+                    module: module.join("."),
+                    ..Default::default()
                 }
-                "trait-name" => {
-                    last_trait_name = text.to_string();
+            })
+            .collect()
+    }
+
+    /// names of inline modules which contain the node, outermost first
+    fn inline_module(node: Node, code: &str) -> Vec<String> {
+        let mut module = vec![];
+        let mut parent = node.parent();
+        while let Some(item) = parent {
+            if item.kind() == "mod_item" {
+                if let Some(name) = item.child_by_field_name("name") {
+                    module.insert(0, code[name.byte_range()].to_string());
                 }
-                &_ => {
+            }
+            parent = item.parent();
+        }
+        module
+    }
+
+    /// (path, alias) of leaves under `prefix`
+    fn use_leaves(node: Node, prefix: &str, code: &str, leaves: &mut Vec<(String, Option<String>)>) {
+        let text = |node: Node| code[node.byte_range()].split_whitespace().collect::<String>();
+        let join = |path: &str| match (prefix.is_empty(), path.is_empty()) {
+            (true, _) => path.to_string(),
+            (false, true) => prefix.to_string(),
+            (false, false) => format!("{}::{}", prefix, path),
+        };
 
+        match node.kind() {
+            "scoped_use_list" => {
+                let path = node.child_by_field_name("path").map(text).unwrap_or_default();
+                if let Some(list) = node.child_by_field_name("list") {
+                    RustIdent::use_leaves(list, &join(&path), code, leaves);
                 }
             }
+            "use_list" => {
+                let mut cursor = node.walk();
+                for child in node.named_children(&mut cursor) {
+                    RustIdent::use_leaves(child, prefix, code, leaves);
+                }
+            }
+            "use_as_clause" => {
+                let path = node.child_by_field_name("path").map(text).unwrap_or_default();
+                let alias = node.child_by_field_name("alias").map(text);
+                leaves.push((join(&path), alias));
+            }
+            // `self` of `use a::{self, b}` is `a`
+            "self" if !prefix.is_empty() => leaves.push((prefix.to_string(), None)),
+            "use_wildcard" | "identifier" | "scoped_identifier" | "crate" | "self" | "super" | "metavariable" => {
+                leaves.push((join(&text(node)), None));
+            }
+            _ => {}
+        }
+    }
+
+    /// items of file or inline module, `module` is the path of inline modules. `mod a;` is an
+    /// import of module `self::a`, its items are parsed from its own file
+    fn insert_items(code_file: &mut CodeFile, parent: Node, module: &[String], code: &str, impls: &mut Impls) {
+        let mut cursor = parent.walk();
+        for item in parent.named_children(&mut cursor) {
+            let name = item.child_by_field_name("name").map(|name| &code[name.byte_range()]);
+            match (item.kind(), name) {
+                ("struct_item", Some(name)) => {
+                    let mut class = RustIdent::create_class(item, name, module, code);
+                    class.constant = RustIdent::struct_fields(item, code);
+                    code_file.classes.push(class);
+                }
+                ("enum_item", Some(name)) => {
+                    let mut class = RustIdent::create_class(item, name, module, code);
+                    class.kind = ClassKind::Enum;
+                    class.constant = RustIdent::enum_variants(item, name, code);
+                    code_file.classes.push(class);
+                }
+                ("trait_item", Some(name)) => {
+                    let mut class = RustIdent::create_class(item, name, module, code);
+                    class.kind = ClassKind::Interface;
+                    class.is_abstract = true;
+                    if let Some(bounds) = item.child_by_field_name("bounds") {
+                        let mut cursor = bounds.walk();
+                        for bound in bounds.named_children(&mut cursor).filter(|bound| bound.kind() != "lifetime") {
+                            class.extends.push(RustIdent::type_name(bound, code));
+                        }
+                    }
+                    if let Some(body) = item.child_by_field_name("body") {
+                        class.functions = RustIdent::functions(body, code);
+                    }
+                    code_file.classes.push(class);
+                }
+                ("function_item", Some(name)) => {
+                    code_file.functions.push(RustIdent::create_function(item, name, code));
+                }
+                ("impl_item", _) => {
+                    let type_name = match item.child_by_field_name("type") {
+                        Some(typ) => RustIdent::type_name(typ, code),
+                        None => { continue; }
+                    };
 
-            if capture.node.start_position().row >= last_class_end_line {
-                if !class.name.is_empty() {
-                    code_file.classes.push(class.clone());
-                    class = CodeClass::default();
+                    if let Some(trait_node) = item.child_by_field_name("trait") {
+                        impls.traits.entry(type_name.clone()).or_default()
+                            .push(RustIdent::type_name(trait_node, code));
+                    }
+                    if let Some(body) = item.child_by_field_name("body") {
+                        impls.functions.entry(type_name.clone()).or_default()
+                            .extend(RustIdent::functions(body, code));
+                    }
+                    if !impls.types.contains(&type_name) {
+                        impls.types.push(type_name);
+                    }
                 }
+                ("mod_item", Some(name)) => {
+                    match item.child_by_field_name("body") {
+                        Some(body) => {
+                            let mut inner = module.to_vec();
+                            inner.push(name.to_string());
+                            RustIdent::insert_items(code_file, body, &inner, code, impls);
+                        }
+                        None => {
+                            let import = format!("self::{}", name);
+                            code_file.imports.push(import.clone());
+                            code_file.code_imports.push(CodeImport {
+                                name: PackageUnify::from_rust_import(&import, false),
+                                import,
+                                source: code[item.byte_range()].to_string(),
+                                line: item.start_position().row,
+                                module: module.join("."),
+                                ..Default::default()
+                            });
+                        }
+                    }
+                }
+                _ => {}
             }
         }
+    }
 
-        for clz in code_file.classes.iter_mut() {
-            if let Some(function) = impl_functions.get(clz.name.as_str()) {
-                clz.functions = function.clone();
+    fn create_class(item: Node, name: &str, module: &[String], code: &str) -> CodeClass {
+        let mut class = CodeClass {
+            name: name.to_string(),
+            package: module.join("."),
+            visibility: RustIdent::visibility(item, code),
+            ..Default::default()
+        };
+        RustIdent::insert_location(&mut class, item);
+        class
+    }
+
+    fn create_function(item: Node, name: &str, code: &str) -> CodeFunction {
+        let mut function = RustIdent::create_function_by_node(item, name);
+        function.visibility = RustIdent::visibility(item, code);
+        function
+    }
+
+    /// `pub`, `pub(crate)` or `pub(in crate::a)` as written, empty for private items
+    fn visibility(item: Node, code: &str) -> String {
+        let mut cursor = item.walk();
+        let visibility = item.children(&mut cursor)
+            .find(|child| child.kind() == "visibility_modifier")
+            .map(|modifier| code[modifier.byte_range()].split_whitespace().collect::<String>());
+        visibility.unwrap_or_default()
+    }
+
+    /// functions of `impl` or `trait` body, with or without default body
+    fn functions(body: Node, code: &str) -> Vec<CodeFunction> {
+        let mut functions = vec![];
+        let mut cursor = body.walk();
+        for child in body.named_children(&mut cursor) {
+            if child.kind() != "function_item" && child.kind() != "function_signature_item" {
+                continue;
             }
 
-            if let Some(trait_name) = trait_struct_map.get(clz.name.as_str()) {
-                clz.implements.push(trait_name.to_string());
+            if let Some(name) = child.child_by_field_name("name") {
+                functions.push(RustIdent::create_function(child, &code[name.byte_range()], code));
             }
         }
+        functions
+    }
 
-        code_file
+    /// `Level` of `Level<'a, T>`, `Display` of `fmt::Display`
+    fn type_name(node: Node, code: &str) -> String {
+        let text = &code[node.byte_range()];
+        let text = text.split('<').next().unwrap_or(text);
+        text.rsplit("::").next().unwrap_or(text).trim().to_string()
+    }
+
+    /// variants are constants of enum
+    fn enum_variants(item_node: Node, enum_name: &str, code: &str) -> Vec<ClassConstant> {
+        let body = match item_node.child_by_field_name("body") {
+            Some(body) => body,
+            None => { return vec![]; }
+        };
+
+        let mut variants = vec![];
+        let mut cursor = body.walk();
+        for variant in body.named_children(&mut cursor).filter(|child| child.kind() == "enum_variant") {
+            if let Some(name) = variant.child_by_field_name("name") {
+                variants.push(ClassConstant {
+                    name: code[name.byte_range()].to_string(),
+                    typ: enum_name.to_string(),
+                    is_constant: true,
+                });
+            }
+        }
+        variants
+    }
+}
+
+impl RustIdent {
+    /// named fields, or `0`, `1`.. for tuple struct
+    fn struct_fields(item_node: Node, code: &str) -> Vec<ClassConstant> {
+        let body = match item_node.child_by_field_name("body") {
+            Some(body) => body,
+            None => { return vec![]; }
+        };
+
+        let mut fields = vec![];
+        let mut cursor = body.walk();
+        for child in body.children(&mut cursor) {
+            let typ = child.child_by_field_name("type")
+                .map(|typ| code[typ.byte_range()].to_string())
+                .unwrap_or_default();
+
+            match child.kind() {
+                "field_declaration" => {
+                    if let Some(name) = child.child_by_field_name("name") {
+                        fields.push(ClassConstant { name: code[name.byte_range()].to_string(), typ, is_constant: false });
+                    }
+                }
+                "visibility_modifier" | "attribute_item" => {}
+                _ if body.kind() == "ordered_field_declaration_list" && child.is_named() => {
+                    let typ = code[child.byte_range()].to_string();
+                    fields.push(ClassConstant { name: fields.len().to_string(), typ, is_constant: false });
+                }
+                _ => {}
+            }
+        }
+
+        fields
     }
 }
 
 impl CodeIdent for RustIdent {
+    fn branch_kinds() -> &'static BranchKinds {
+        &RUST_BRANCHES
+    }
+
     fn parse(code: &str) -> CodeFile {
         let mut ident = RustIdent::new();
         RustIdent::do_parse(code, &mut ident)
@@ -135,6 +378,8 @@ impl CodeIdent for RustIdent {
 
 #[cfg(test)]
 mod tests {
+    use guarding_core::domain::code_class::ClassKind;
+
     use crate::code_ident::CodeIdent;
     use crate::rust_ident::RustIdent;
 
@@ -146,6 +391,54 @@ mod tests {
         assert_eq!(1, file.imports.len());
     }
 
+    #[test]
+    fn should_expand_use_trees() {
+        let source_code = "use std::collections::{HashMap, hash_map::{self, Entry as MapEntry}};
+use crate::domain::*;
+use serde;
+pub use super::code_ident::CodeIdent;
+pub(crate) use self::complexity::{BranchKinds, measure as measure_complexity};
+";
+        let file = RustIdent::parse(source_code);
+
+        assert_eq!(vec![
+            "std::collections::HashMap",
+            "std::collections::hash_map",
+            "std::collections::hash_map::Entry",
+            "crate::domain::*",
+            "serde",
+            "super::code_ident::CodeIdent",
+            "self::complexity::BranchKinds",
+            "self::complexity::measure",
+        ], file.imports);
+
+        let imports = &file.code_imports;
+        assert_eq!("std.collections.HashMap", imports[0].name);
+        assert_eq!(Some("MapEntry".to_string()), imports[2].alias);
+        assert!(imports[3].is_wildcard);
+        assert_eq!("crate.domain", imports[3].name);
+        assert!(!imports[4].is_reexport);
+        assert!(imports[5].is_reexport);
+        assert_eq!(4, imports[7].line);
+        assert_eq!(Some("measure_complexity".to_string()), imports[7].alias);
+    }
+
+    #[test]
+    fn should_record_inline_module_of_use() {
+        let source_code = "use super::Repository;
+
+mod tests {
+    mod nested {
+        use super::*;
+    }
+}
+";
+        let file = RustIdent::parse(source_code);
+
+        assert_eq!("", file.code_imports[0].module);
+        assert_eq!("tests.nested", file.code_imports[1].module);
+    }
+
     #[test]
     fn should_parse_basic_struct() {
         let source_code = "pub struct RustIdent {
@@ -188,4 +481,181 @@ impl Default for RustIdent {
 
         assert_eq!("Default", file.classes[0].implements[0]);
     }
+
+    #[test]
+    fn should_parse_enum_trait_and_functions() {
+        let source_code = "pub enum Level<T> {
+    Low,
+    High(T),
+}
+
+impl<T> fmt::Display for Level<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Ok(())
+    }
+}
+
+pub trait Parser: Clone + Send + 'static {
+    fn parse(&self, text: &str) -> usize;
+
+    fn name(&self) -> String {
+        String::new()
+    }
+}
+
+pub fn run() {}
+
+impl CodeFile {
+    fn size(&self) -> usize { 0 }
+}
+
+mod tests {
+    struct Fixture;
+
+    fn helper() {}
+}
+";
+        let file = RustIdent::parse(source_code);
+        let names: Vec<&str> = file.classes.iter().map(|class| class.name.as_str()).collect();
+        assert_eq!(vec!["Level", "Parser", "Fixture"], names);
+
+        let level = &file.classes[0];
+        assert_eq!(ClassKind::Enum, level.kind);
+        assert_eq!(vec!["Low", "High"], level.constant.iter().map(|c| c.name.as_str()).collect::<Vec<&str>>());
+        assert_eq!(vec!["Display"], level.implements);
+        assert_eq!("fmt", level.functions[0].name);
+
+        let parser = &file.classes[1];
+        assert_eq!(ClassKind::Interface, parser.kind);
+        assert!(parser.is_abstract);
+        assert_eq!(vec!["Clone", "Send"], parser.extends);
+        assert_eq!(2, parser.functions.len());
+
+        assert_eq!("", level.package);
+        assert_eq!("tests", file.classes[2].package);
+
+        let functions: Vec<&str> = file.functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(vec!["run", "helper", "size"], functions);
+    }
+
+    #[test]
+    fn should_record_mod_declarations_and_visibility() {
+        let source_code = "pub mod domain;
+mod infra;
+
+pub(crate) struct Service;
+
+impl Service {
+    pub fn run(&self) {}
+    fn stop(&self) {}
+}
+
+mod inner {
+    pub(in crate::inner) mod detail;
+}
+";
+        let file = RustIdent::parse(source_code);
+        assert_eq!(vec!["self::domain", "self::infra", "self::detail"], file.imports);
+        assert_eq!("pub mod domain;", file.code_imports[0].source);
+        assert_eq!("inner", file.code_imports[2].module);
+
+        let service = &file.classes[0];
+        assert_eq!("pub(crate)", service.visibility);
+        assert_eq!("pub", service.functions[0].visibility);
+        assert_eq!("", service.functions[1].visibility);
+    }
+
+    #[test]
+    fn should_count_struct_fields() {
+        let source_code = "pub struct Point {
+    x: usize,
+    y: usize,
+    name: String,
+}
+
+pub struct Empty;
+";
+        let file = RustIdent::parse(source_code);
+        assert_eq!(2, file.classes.len());
+
+        let point = &file.classes[0];
+        assert_eq!(3, point.constant.len());
+        assert_eq!("String", point.constant[2].typ);
+        assert_eq!(0, point.start.row);
+        assert_eq!(4, point.end.row);
+
+        assert_eq!(0, file.classes[1].constant.len());
+    }
+
+    #[test]
+    fn should_measure_function_complexity() {
+        let source_code = "pub struct Level {}
+
+impl Level {
+    fn parse(text: &str) -> usize {
+        match text {
+            \"a\" => 1,
+            \"b\" if text.len() > 1 || text.is_empty() => 2,
+            _ => {
+                for c in text.chars() {
+                    if c == 'x' {
+                        return 3;
+                    } else if c == 'y' {
+                        return 4;
+                    }
+                }
+                0
+            }
+        }
+    }
+}
+";
+        let file = RustIdent::parse(source_code);
+        let function = &file.classes[0].functions[0];
+
+        assert_eq!(8, function.complexity);
+        assert_eq!(3, function.nesting);
+    }
+
+    #[test]
+    fn should_skip_wildcard_arm_and_count_match_guards() {
+        let source_code = "fn sign(n: i32) -> i32 {
+    match n {
+        0 => 0,
+        _ => 1,
+    }
+}
+
+fn check(n: Option<i32>) -> i32 {
+    match n {
+        Some(v) if v > 0 => v,
+        Some(_) => 0,
+        None => -1,
+    }
+}
+";
+        let file = RustIdent::parse(source_code);
+
+        assert_eq!(2, file.functions[0].complexity);
+        assert_eq!(5, file.functions[1].complexity);
+    }
+
+    #[test]
+    fn should_not_count_branches_of_closures() {
+        let source_code = "fn positive(items: &[i32]) -> Vec<i32> {
+    if items.is_empty() {
+        return vec![];
+    }
+
+    items.iter()
+        .filter(|item| if **item > 0 { true } else { false })
+        .map(|item| match item { 1 => 1, _ => item * 2 })
+        .collect()
+}
+";
+        let file = RustIdent::parse(source_code);
+
+        assert_eq!(2, file.functions[0].complexity);
+        assert_eq!(1, file.functions[0].nesting);
+    }
 }
@@ -0,0 +1,426 @@
+use tree_sitter::{Language, Node, Parser, Query, QueryCursor};
+
+use guarding_core::domain::code_annotation::CodeAnnotation;
+use guarding_core::domain::code_class::{ClassConstant, CodeClass};
+use guarding_core::domain::code_file::CodeFile;
+use guarding_core::domain::code_import::CodeImport;
+use crate::code_ident::CodeIdent;
+use crate::identify::complexity::BranchKinds;
+
+const TS_QUERY: &str = "
+(import_statement
+	source: (string) @source)
+(import_require_clause
+	(string) @source)
+(export_statement
+	source: (string) @source)
+(call_expression
+	function: (identifier) @require
+	arguments: (arguments . (string) @source)
+	(#eq? @require \"require\"))
+(call_expression
+	function: (import)
+	arguments: (arguments . (string) @source))
+
+(class_declaration
+	name: (type_identifier) @class-name)
+(abstract_class_declaration
+	name: (type_identifier) @class-name)
+(interface_declaration
+	name: (type_identifier) @class-name)
+
+(function_declaration
+	name: (identifier) @function-name)
+(variable_declarator
+	name: (identifier) @variable-name
+	value: [(arrow_function) (function)])
+";
+
+const TS_BRANCHES: BranchKinds = BranchKinds {
+    decisions: &["if_statement", "for_statement", "for_in_statement", "while_statement", "do_statement", "catch_clause", "ternary_expression", "switch_case"],
+    nestings: &["if_statement", "for_statement", "for_in_statement", "while_statement", "do_statement", "switch_statement", "try_statement"],
+    boundaries: &["function_declaration", "generator_function_declaration", "function", "arrow_function", "class_declaration", "abstract_class_declaration", "class", "method_definition"],
+};
+
+/// TypeScript ident for `.ts`, `.mts` and `.cts`, and TSX by `TsIdent::parse_tsx`
+pub struct TsIdent {
+    parser: Parser,
+    query: Query,
+}
+
+impl TsIdent {
+    fn new(language: Language) -> TsIdent {
+        let mut parser = Parser::new();
+        parser.set_language(language).unwrap();
+
+        let query = Query::new(language, TS_QUERY)
+            .map_err(|e| println!("Query compilation failed: {:?}", e)).unwrap();
+        TsIdent { parser, query }
+    }
+
+    pub fn parse_tsx(code: &str) -> CodeFile {
+        let mut ident = TsIdent::new(tree_sitter_typescript::language_tsx());
+        TsIdent::do_parse(code, &mut ident)
+    }
+}
+
+impl TsIdent {
+    fn do_parse(code: &str, ident: &mut TsIdent) -> CodeFile {
+        let text_callback = |n: Node| std::iter::once(code[n.byte_range()].as_bytes());
+        let tree = ident.parser.parse(code, None).unwrap();
+
+        let mut query_cursor = QueryCursor::new();
+        let captures = query_cursor.captures(&ident.query, tree.root_node(), text_callback);
+
+        let mut code_file = CodeFile::default();
+
+        for (mat, capture_index) in captures {
+            let capture = mat.captures[capture_index];
+            let capture_name = &ident.query.capture_names()[capture.index as usize];
+
+            let text = capture.node.utf8_text((&code).as_ref()).unwrap_or("");
+            match capture_name.as_str() {
+                "source" => {
+                    // type-only imports, like `import type { User } from './user'`, are dependencies too,
+                    // so are re-exports, `require('./x')` and `import('./x')`
+                    code_file.imports.push(text.to_string());
+                    code_file.code_imports.push(TsIdent::create_import(capture.node, text, code));
+                }
+                "class-name" => {
+                    let class_node = capture.node.parent().unwrap();
+                    if TsIdent::is_top_level(class_node) {
+                        code_file.classes.push(TsIdent::create_class(class_node, text, code));
+                    }
+                }
+                "function-name" => {
+                    if TsIdent::is_top_level(capture.node.parent().unwrap()) {
+                        code_file.functions.push(TsIdent::create_function(capture, text));
+                    }
+                }
+                "variable-name" => {
+                    // `export const handle = () => {}`
+                    let declarator = capture.node.parent().unwrap();
+                    let is_top_level = matches!(declarator.parent(), Some(declaration) if TsIdent::is_top_level(declaration));
+                    if let (true, Some(value)) = (is_top_level, declarator.child_by_field_name("value")) {
+                        code_file.functions.push(TsIdent::create_function_by_node(value, text));
+                    }
+                }
+                &_ => {}
+            }
+        }
+
+        TsIdent::insert_lines(&mut code_file, tree.root_node(), code);
+
+        code_file
+    }
+
+    /// import of the statement which contains `source` string: `import type` is type only,
+    /// `export ... from` is a re-export, `import * as ns` and `export * from` are wildcards, and
+    /// `ns` of `import * as ns` or `import ns = require(..)` is the alias
+    fn create_import(source: Node, text: &str, code: &str) -> CodeImport {
+        let mut import = CodeImport {
+            name: text.trim_matches(|c| c == '"' || c == '\'' || c == '`').to_string(),
+            import: text.to_string(),
+            source: text.to_string(),
+            line: source.start_position().row,
+            ..Default::default()
+        };
+
+        let statement = match source.parent() {
+            Some(parent) if parent.kind() == "arguments" || parent.kind() == "import_require_clause" => {
+                parent.parent().unwrap_or(parent)
+            }
+            Some(parent) => parent,
+            None => { return import; }
+        };
+        import.source = code[statement.byte_range()].to_string();
+        import.is_reexport = statement.kind() == "export_statement";
+
+        let mut cursor = statement.walk();
+        for child in statement.children(&mut cursor) {
+            match child.kind() {
+                "type" => import.is_type_only = true,
+                "*" => import.is_wildcard = true,
+                "import_clause" => {
+                    let mut cursor = child.walk();
+                    let namespace = child.named_children(&mut cursor).find(|node| node.kind() == "namespace_import");
+                    if let Some(identifier) = namespace.and_then(|namespace| namespace.named_child(0)) {
+                        import.is_wildcard = true;
+                        import.alias = Some(code[identifier.byte_range()].to_string());
+                    }
+                }
+                "import_require_clause" => {
+                    import.alias = child.named_child(0).map(|identifier| code[identifier.byte_range()].to_string());
+                }
+                _ => {}
+            }
+        }
+
+        import
+    }
+
+    /// declaration of program, exported or not
+    fn is_top_level(node: Node) -> bool {
+        match node.parent() {
+            Some(parent) if parent.kind() == "program" => true,
+            Some(parent) if parent.kind() == "export_statement" => {
+                matches!(parent.parent(), Some(program) if program.kind() == "program")
+            }
+            _ => false
+        }
+    }
+
+    /// class, abstract class or interface
+    fn create_class(class_node: Node, name: &str, code: &str) -> CodeClass {
+        let mut class = CodeClass {
+            name: name.to_string(),
+            is_abstract: class_node.kind() != "class_declaration",
+            ..Default::default()
+        };
+
+        // decorators before `export` belong to the export statement
+        match class_node.parent().filter(|parent| parent.kind() == "export_statement") {
+            Some(export) => {
+                TsIdent::insert_location(&mut class, export);
+                class.annotations.extend(TsIdent::decorators(export, code));
+            }
+            None => TsIdent::insert_location(&mut class, class_node),
+        }
+        class.annotations.extend(TsIdent::decorators(class_node, code));
+
+        let mut cursor = class_node.walk();
+        for child in class_node.children(&mut cursor) {
+            match child.kind() {
+                "class_heritage" => {
+                    let mut cursor = child.walk();
+                    for clause in child.children(&mut cursor) {
+                        let types = TsIdent::type_names(clause, code);
+                        match clause.kind() {
+                            "extends_clause" => class.extends.extend(types),
+                            "implements_clause" => class.implements.extend(types),
+                            _ => {}
+                        }
+                    }
+                }
+                "extends_clause" => {
+                    class.extends.extend(TsIdent::type_names(child, code));
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(body) = class_node.child_by_field_name("body") {
+            TsIdent::insert_members(&mut class, body, code);
+        }
+
+        class
+    }
+
+    /// methods and fields, a member decorator is the sibling before the member
+    fn insert_members(class: &mut CodeClass, body: Node, code: &str) {
+        let mut decorators: Vec<CodeAnnotation> = vec![];
+
+        let mut cursor = body.walk();
+        for child in body.children(&mut cursor) {
+            match child.kind() {
+                "decorator" => {
+                    decorators.push(TsIdent::decorator(child, code));
+                    continue;
+                }
+                "method_definition" | "method_signature" | "abstract_method_signature" => {
+                    if let Some(name) = child.child_by_field_name("name") {
+                        let mut function = TsIdent::create_function_by_node(child, &code[name.byte_range()]);
+                        function.annotations = decorators.clone();
+                        class.functions.push(function);
+                    }
+                }
+                "public_field_definition" | "property_signature" => {
+                    if let Some(name) = child.child_by_field_name("name") {
+                        let typ = child.child_by_field_name("type")
+                            .map(|typ| code[typ.byte_range()].trim_start_matches(':').trim().to_string())
+                            .unwrap_or_default();
+                        let is_constant = TsIdent::has_keyword(child, code, "static") && TsIdent::has_keyword(child, code, "readonly");
+
+                        class.constant.push(ClassConstant { name: code[name.byte_range()].to_string(), typ, is_constant });
+                    }
+                }
+                _ => {}
+            }
+
+            decorators.clear();
+        }
+    }
+
+    /// `Base` of `extends Base<T>`, `Repository` of `implements Repository<User>`
+    fn type_names(clause: Node, code: &str) -> Vec<String> {
+        let mut cursor = clause.walk();
+        let names = clause.named_children(&mut cursor)
+            .map(|typ| {
+                let text = &code[typ.byte_range()];
+                text.split('<').next().unwrap_or(text).trim().to_string()
+            })
+            .collect();
+        names
+    }
+
+    fn decorators(node: Node, code: &str) -> Vec<CodeAnnotation> {
+        let mut cursor = node.walk();
+        let decorators = node.children_by_field_name("decorator", &mut cursor)
+            .map(|decorator| TsIdent::decorator(decorator, code))
+            .collect();
+        decorators
+    }
+
+    /// `Component` of `@Component({ selector: 'app' })`
+    fn decorator(node: Node, code: &str) -> CodeAnnotation {
+        let name = node.named_child(0)
+            .map(|expr| match expr.child_by_field_name("function") {
+                Some(function) => &code[function.byte_range()],
+                None => &code[expr.byte_range()],
+            })
+            .unwrap_or("");
+
+        CodeAnnotation::new(name)
+    }
+
+    fn has_keyword(node: Node, code: &str, keyword: &str) -> bool {
+        let mut cursor = node.walk();
+        let has_keyword = node.children(&mut cursor)
+            .any(|child| !child.is_named() && &code[child.byte_range()] == keyword);
+        has_keyword
+    }
+}
+
+impl CodeIdent for TsIdent {
+    fn branch_kinds() -> &'static BranchKinds {
+        &TS_BRANCHES
+    }
+
+    fn parse(code: &str) -> CodeFile {
+        let mut ident = TsIdent::new(tree_sitter_typescript::language_typescript());
+        TsIdent::do_parse(code, &mut ident)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::code_ident::CodeIdent;
+    use crate::ts_ident::TsIdent;
+
+    #[test]
+    fn should_parse_imports() {
+        let source_code = "import { Injectable } from '@angular/core';
+import type { User } from '@app/domain/user';
+import * as path from 'path';
+import fs = require('fs');
+export * from './models';
+const routes = () => import('./routes');
+";
+        let file = TsIdent::parse(source_code);
+
+        assert_eq!(vec!["'@angular/core'", "'@app/domain/user'", "'path'", "'fs'", "'./models'", "'./routes'"], file.imports);
+
+        let imports = &file.code_imports;
+        assert_eq!("@angular/core", imports[0].name);
+        assert!(!imports[0].is_type_only);
+        assert!(imports[1].is_type_only);
+        assert!(imports[2].is_wildcard);
+        assert_eq!(Some("path".to_string()), imports[2].alias);
+        assert_eq!(Some("fs".to_string()), imports[3].alias);
+        assert_eq!("import fs = require('fs');", imports[3].source);
+        assert!(imports[4].is_reexport);
+        assert!(imports[4].is_wildcard);
+        assert_eq!("import('./routes')", imports[5].source);
+        assert_eq!(5, imports[5].line);
+    }
+
+    #[test]
+    fn should_parse_class_with_heritage_and_decorators() {
+        let source_code = "@Injectable()
+export class UserService extends BaseService<User> implements Repository<User>, OnInit {
+  static readonly MAX_SIZE: number = 20;
+  private name: string;
+
+  @Log
+  @Cache({ ttl: 10 })
+  find(id: number): User {
+    return null;
+  }
+
+  ngOnInit() {}
+}
+";
+        let file = TsIdent::parse(source_code);
+
+        let class = &file.classes[0];
+        assert_eq!("UserService", class.name);
+        assert_eq!(0, class.start.row);
+        assert_eq!(vec!["BaseService"], class.extends);
+        assert_eq!(vec!["Repository", "OnInit"], class.implements);
+        assert_eq!("Injectable", class.annotations[0].name);
+
+        assert_eq!(2, class.constant.len());
+        assert!(class.constant[0].is_constant);
+        assert_eq!("string", class.constant[1].typ);
+        assert!(!class.constant[1].is_constant);
+
+        assert_eq!(2, class.functions.len());
+        let annotations: Vec<&str> = class.functions[0].annotations.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(vec!["Log", "Cache"], annotations);
+        assert_eq!(0, class.functions[1].annotations.len());
+    }
+
+    #[test]
+    fn should_parse_interface_and_abstract_class() {
+        let source_code = "export interface Repository<T> extends Closeable, Iterable<T> {
+  size: number;
+  find(id: number): T;
+}
+
+abstract class Shape {
+  abstract area(): number;
+}
+";
+        let file = TsIdent::parse(source_code);
+
+        let interface = &file.classes[0];
+        assert!(interface.is_abstract);
+        assert_eq!(vec!["Closeable", "Iterable"], interface.extends);
+        assert_eq!(1, interface.constant.len());
+        assert_eq!("find", interface.functions[0].name);
+
+        assert!(file.classes[1].is_abstract);
+        assert_eq!("area", file.classes[1].functions[0].name);
+    }
+
+    #[test]
+    fn should_parse_exported_functions() {
+        let source_code = "export function handle(req: Request): void {
+  if (req) {}
+}
+
+export const parse = (text: string) => text.length > 0 ? 1 : 0;
+
+function local() {
+  const inner = () => {};
+}
+";
+        let file = TsIdent::parse(source_code);
+
+        let names: Vec<&str> = file.functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(vec!["handle", "parse", "local"], names);
+        assert_eq!(2, file.functions[0].complexity);
+        assert_eq!(2, file.functions[1].complexity);
+    }
+
+    #[test]
+    fn should_parse_tsx() {
+        let source_code = "export function App(props: Props) {
+  return <div className=\"app\">{props.title}</div>;
+}
+";
+        let file = TsIdent::parse_tsx(source_code);
+
+        assert_eq!("App", file.functions[0].name);
+    }
+}
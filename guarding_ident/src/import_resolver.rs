@@ -0,0 +1,1072 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use guarding_core::domain::code_dependency::CodeDependency;
+use guarding_core::domain::code_file::CodeFile;
+use guarding_parser::support::package_unify::PackageUnify;
+use serde_json::Value;
+
+const JS_EXTENSIONS: [&str; 8] = ["js", "jsx", "mjs", "cjs", "ts", "tsx", "mts", "cts"];
+const CPP_EXTENSIONS: [&str; 5] = ["c", "h", "cpp", "hpp", "cc"];
+
+/// Resolve raw imports of every language into `CodeDependency`, so that access rules can
+/// use the same dotted package form for Java, Kotlin, Rust, JavaScript, Go, Python, C/C++ and C#.
+pub struct ImportResolver {
+    root: PathBuf,
+    /// normalized path -> path of model
+    files: HashMap<PathBuf, String>,
+    /// normalized path -> package of model
+    file_packages: HashMap<PathBuf, String>,
+    packages: HashSet<String>,
+    /// (package, type name) -> path, for languages which file is named by type
+    types: HashMap<(String, String), String>,
+    /// crate name -> crate dir, crates in current project
+    crates: HashMap<String, PathBuf>,
+    /// (crate name, module path) -> file path
+    rust_modules: HashMap<(String, Vec<String>), String>,
+    /// normalized path -> (crate name, module path)
+    rust_files: HashMap<PathBuf, (String, Vec<String>)>,
+    /// `compilerOptions.paths` of `tsconfig.json` in root: alias pattern -> target patterns
+    ts_paths: Vec<(String, Vec<PathBuf>)>,
+}
+
+impl ImportResolver {
+    pub fn new(root: &Path, models: &[CodeFile]) -> ImportResolver {
+        let mut resolver = ImportResolver {
+            root: normalize(root),
+            files: Default::default(),
+            file_packages: Default::default(),
+            packages: Default::default(),
+            types: Default::default(),
+            crates: Default::default(),
+            rust_modules: Default::default(),
+            rust_files: Default::default(),
+            ts_paths: ts_paths(root),
+        };
+
+        let mut crate_roots = CrateRoots::new(&resolver.root);
+        for file in models {
+            let path = normalize(Path::new(&file.path));
+            resolver.files.insert(path.clone(), file.path.clone());
+            resolver.file_packages.insert(path.clone(), file.package.clone());
+
+            if !file.package.is_empty() {
+                resolver.packages.insert(file.package.clone());
+                if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                    resolver.types.insert((file.package.clone(), stem.to_string()), file.path.clone());
+                }
+            }
+
+            if language_of(&file.file_name) == Language::Rust {
+                if let Some((crate_name, crate_dir, module)) = crate_roots.module_path(&path) {
+                    resolver.crates.insert(crate_name.clone(), crate_dir);
+                    resolver.rust_modules.insert((crate_name.clone(), module.clone()), file.path.clone());
+                    resolver.rust_files.insert(path, (crate_name, module));
+                }
+            }
+        }
+
+        resolver
+    }
+
+    /// fill `CodeFile::dependencies` of all models
+    pub fn resolve_models(root: &Path, models: &mut [CodeFile]) {
+        let resolver = ImportResolver::new(root, models);
+        let dependencies: Vec<Vec<CodeDependency>> = models.iter()
+            .map(|file| resolver.resolve(file))
+            .collect();
+
+        for (file, deps) in models.iter_mut().zip(dependencies) {
+            file.dependencies = deps;
+        }
+    }
+
+    pub fn resolve(&self, file: &CodeFile) -> Vec<CodeDependency> {
+        let is_rust = language_of(&file.file_name) == Language::Rust;
+        file.imports.iter()
+            .enumerate()
+            .map(|(index, imp)| match file.code_imports.get(index) {
+                // `use` in inline module, like `mod tests { use super::*; }`, is relative to it
+                Some(code_import) if is_rust && code_import.import == *imp => {
+                    self.resolve_rust(file, imp, &code_import.module)
+                }
+                _ => self.resolve_import(file, imp),
+            })
+            .collect()
+    }
+
+    pub fn resolve_import(&self, file: &CodeFile, import: &str) -> CodeDependency {
+        match language_of(&file.file_name) {
+            Language::Java => self.resolve_java(file, import),
+            Language::Kotlin => self.resolve_qualified(import),
+            Language::CSharp => self.resolve_namespace(import),
+            Language::Rust => self.resolve_rust(file, import, ""),
+            Language::JavaScript => self.resolve_js(file, import),
+            Language::Go => self.resolve_go(import),
+            Language::Python => self.resolve_python(file, import),
+            Language::Cpp => self.resolve_include(file, import),
+            Language::Other => CodeDependency {
+                source: import.to_string(),
+                name: import.to_string(),
+                package: import.to_string(),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// a static import, like `org.junit.Assert.assertEquals` or `org.junit.Assert.*`, depends on
+    /// its class
+    fn resolve_java(&self, file: &CodeFile, import: &str) -> CodeDependency {
+        let is_static = file.code_imports.iter().any(|imp| imp.import == import && imp.is_static);
+        if !is_static {
+            return self.resolve_qualified(import);
+        }
+
+        let class = import.rsplit_once('.').map(|(class, _)| class).unwrap_or(import);
+        let mut dependency = self.resolve_qualified(class);
+        dependency.source = import.to_string();
+        dependency
+    }
+
+    /// Java style: `com.phodal.Foo`, `com.phodal.*`
+    fn resolve_qualified(&self, import: &str) -> CodeDependency {
+        let mut dependency = CodeDependency {
+            source: import.to_string(),
+            ..Default::default()
+        };
+
+        let name = import.trim_end_matches(".*");
+        dependency.name = name.to_string();
+
+        if import.ends_with(".*") || self.packages.contains(name) {
+            dependency.package = name.to_string();
+            dependency.is_wildcard = import.ends_with(".*");
+            return dependency;
+        }
+
+        match name.rfind('.') {
+            Some(pos) => {
+                let package = &name[..pos];
+                let type_name = &name[pos + 1..];
+                dependency.package = package.to_string();
+                dependency.file = self.types.get(&(package.to_string(), type_name.to_string())).cloned();
+            }
+            None => {
+                dependency.package = name.to_string();
+            }
+        }
+
+        dependency
+    }
+
+    /// C# `using` always import a namespace
+    fn resolve_namespace(&self, import: &str) -> CodeDependency {
+        CodeDependency {
+            source: import.to_string(),
+            name: import.to_string(),
+            package: import.to_string(),
+            file: None,
+            is_wildcard: true,
+        }
+    }
+
+    /// Go imports a package directory: `github.com/inherd/app/domain`
+    fn resolve_go(&self, import: &str) -> CodeDependency {
+        let package = import.replace('/', ".");
+        CodeDependency {
+            source: import.to_string(),
+            name: package.clone(),
+            package,
+            ..Default::default()
+        }
+    }
+
+    /// `app.domain.models.User`, relative `..domain.User` is resolved from the package of `file`
+    fn resolve_python(&self, file: &CodeFile, import: &str) -> CodeDependency {
+        let mut dependency = CodeDependency {
+            source: import.to_string(),
+            ..Default::default()
+        };
+
+        let relative = import.len() - import.trim_start_matches('.').len();
+        let mut parts: Vec<&str> = vec![];
+        if relative > 0 {
+            parts = file.package.split('.').filter(|s| !s.is_empty()).collect();
+            parts.truncate(parts.len().saturating_sub(relative - 1));
+        }
+        parts.extend(import[relative..].split('.').filter(|s| !s.is_empty()));
+
+        dependency.name = parts.join(".");
+
+        // longest prefix which is a package or a module of project
+        for len in (1..=parts.len()).rev() {
+            let prefix = parts[..len].join(".");
+            let package = parts[..len - 1].join(".");
+            if self.packages.contains(&prefix) {
+                dependency.file = self.types.get(&(prefix.clone(), "__init__".to_string())).cloned();
+                dependency.package = prefix;
+                return dependency;
+            }
+
+            if let Some(path) = self.types.get(&(package.clone(), parts[len - 1].to_string())) {
+                dependency.package = package;
+                dependency.file = Some(path.clone());
+                return dependency;
+            }
+        }
+
+        dependency.package = if parts.len() > 1 {
+            parts[..parts.len() - 1].join(".")
+        } else {
+            dependency.name.clone()
+        };
+        dependency
+    }
+
+    /// `drivers/uart.h` is searched from the directory of `file`, then from root. an include of
+    /// project has the package of included file, others like `stdio.h` are dotted by path
+    fn resolve_include(&self, file: &CodeFile, import: &str) -> CodeDependency {
+        let mut dependency = CodeDependency {
+            source: import.to_string(),
+            ..Default::default()
+        };
+
+        let current = normalize(Path::new(&file.path));
+        let base = current.parent().unwrap_or_else(|| Path::new(""));
+        let found = vec![normalize(&base.join(import)), normalize(&self.root.join(import))].into_iter()
+            .find(|path| self.files.contains_key(path));
+
+        let include = Path::new(import);
+        let stem = include.file_stem().and_then(|s| s.to_str()).unwrap_or(import);
+        match found {
+            Some(path) => {
+                let package = self.file_packages.get(&path).cloned().unwrap_or_default();
+                dependency.name = join_dotted(&package, stem);
+                dependency.package = package;
+                dependency.file = self.files.get(&path).cloned();
+            }
+            None => {
+                let parent = match include.parent() {
+                    Some(dir) if !dir.as_os_str().is_empty() => PackageUnify::from_path(dir.to_path_buf()),
+                    _ => "".to_string(),
+                };
+                dependency.name = join_dotted(&parent, stem);
+                dependency.package = if parent.is_empty() { dependency.name.clone() } else { parent };
+            }
+        }
+
+        dependency
+    }
+
+    /// `crate::a::b::C`, `super::b`, `self::c`, `other_crate::a` or `std::path::PathBuf`. a glob,
+    /// like `crate::a::*`, depends on the whole module `a`. `inline` is the dotted path of the
+    /// inline module which contains the `use`, `self` and `super` are relative to it
+    fn resolve_rust(&self, file: &CodeFile, import: &str, inline: &str) -> CodeDependency {
+        let glob = import.strip_suffix("::*");
+        let mut dependency = match glob {
+            Some(module) => self.resolve_rust(file, module, inline),
+            None => self.resolve_rust_path(file, import, inline),
+        };
+
+        if glob.is_some() {
+            dependency.source = import.to_string();
+            dependency.package = dependency.name.clone();
+            dependency.is_wildcard = true;
+        }
+        dependency
+    }
+
+    fn resolve_rust_path(&self, file: &CodeFile, import: &str, inline: &str) -> CodeDependency {
+        let mut dependency = CodeDependency {
+            source: import.to_string(),
+            ..Default::default()
+        };
+
+        let import = import.trim_start_matches("::");
+        let mut segments: Vec<&str> = import.split("::")
+            .filter(|s| !s.is_empty())
+            .collect();
+
+        let current = self.rust_files.get(&normalize(Path::new(&file.path)));
+        let mut module: Vec<String> = vec![];
+        let crate_name = match segments.first() {
+            Some(&"crate") | Some(&"self") | Some(&"super") => {
+                let (crate_name, current_module) = match current {
+                    Some(current) => current.clone(),
+                    None => {
+                        dependency.name = PackageUnify::from_rust_import(import, false);
+                        dependency.package = PackageUnify::from_rust_import(import, true);
+                        return dependency;
+                    }
+                };
+
+                if segments[0] != "crate" {
+                    module = current_module;
+                    module.extend(inline.split('.').filter(|s| !s.is_empty()).map(|s| s.to_string()));
+                }
+
+                while let Some(first) = segments.first() {
+                    match *first {
+                        "crate" | "self" => {}
+                        "super" => { module.pop(); }
+                        _ => break
+                    }
+                    segments.remove(0);
+                }
+
+                crate_name
+            }
+            Some(name) if self.crates.contains_key(*name) => {
+                let name = name.to_string();
+                segments.remove(0);
+                name
+            }
+            _ => {
+                dependency.name = PackageUnify::from_rust_import(import, false);
+                dependency.package = if segments.len() > 1 {
+                    PackageUnify::from_rust_import(import, true)
+                } else {
+                    dependency.name.clone()
+                };
+                return dependency;
+            }
+        };
+
+        module.extend(segments.iter().map(|s| s.to_string()));
+
+        let dotted = |parts: &[String]| {
+            let mut all = vec![crate_name.clone()];
+            all.extend(parts.iter().cloned());
+            all.join(".")
+        };
+
+        dependency.name = dotted(&module);
+
+        // longest module prefix which is a file: `a::b::C` may be item `C` in `a/b.rs`
+        for len in (0..=module.len()).rev() {
+            let prefix = module[..len].to_vec();
+            if let Some(path) = self.rust_modules.get(&(crate_name.clone(), prefix.clone())) {
+                dependency.package = dotted(&prefix);
+                dependency.file = Some(path.clone());
+                return dependency;
+            }
+        }
+
+        let parent = if module.is_empty() { 0 } else { module.len() - 1 };
+        dependency.package = dotted(&module[..parent]);
+        dependency
+    }
+
+    /// `'./say.js'`, `"../lib"`, `'react'`, `'@scope/pkg/sub'`
+    fn resolve_js(&self, file: &CodeFile, import: &str) -> CodeDependency {
+        let mut dependency = CodeDependency {
+            source: import.to_string(),
+            ..Default::default()
+        };
+
+        let spec = import.trim_matches(|c| c == '"' || c == '\'' || c == '`');
+        let (target, found) = if spec.starts_with("./") || spec.starts_with("../") || spec.starts_with('/') {
+            let current = normalize(Path::new(&file.path));
+            let base = current.parent().unwrap_or_else(|| Path::new(""));
+            let target = if spec.starts_with('/') {
+                normalize(&self.root.join(spec.trim_start_matches('/')))
+            } else {
+                normalize(&base.join(spec))
+            };
+
+            let found = self.find_js_file(&target);
+            (target, found)
+        } else {
+            match self.resolve_ts_alias(spec) {
+                Some(resolved) => resolved,
+                None => {
+                    let name = spec.trim_start_matches('@').replace('/', ".");
+                    dependency.name = name.clone();
+                    dependency.package = name;
+                    return dependency;
+                }
+            }
+        };
+
+        let resolved = found.clone().unwrap_or(target);
+
+        let package = self.path_package(resolved.parent().unwrap_or_else(|| Path::new("")));
+        let stem = resolved.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+
+        dependency.name = if stem == "index" { package.clone() } else { join_dotted(&package, stem) };
+        dependency.package = package;
+        dependency.file = found.and_then(|path| self.files.get(&path).cloned());
+
+        dependency
+    }
+
+    /// `@app/domain/user` by `"@app/*": ["src/app/*"]`, the longest alias wins, then the first
+    /// target which is a file of project
+    fn resolve_ts_alias(&self, spec: &str) -> Option<(PathBuf, Option<PathBuf>)> {
+        let (targets, matched) = self.ts_paths.iter()
+            .filter_map(|(alias, targets)| {
+                let matched = match alias.find('*') {
+                    Some(pos) => {
+                        let (prefix, suffix) = (&alias[..pos], &alias[pos + 1..]);
+                        let is_match = spec.len() >= prefix.len() + suffix.len()
+                            && spec.starts_with(prefix) && spec.ends_with(suffix);
+                        if !is_match {
+                            return None;
+                        }
+                        &spec[prefix.len()..spec.len() - suffix.len()]
+                    }
+                    None if alias == spec => "",
+                    None => { return None; }
+                };
+                Some((alias.len(), targets, matched))
+            })
+            .max_by_key(|(len, _, _)| *len)
+            .map(|(_, targets, matched)| (targets, matched))?;
+
+        let candidates: Vec<PathBuf> = targets.iter()
+            .map(|target| normalize(&PathBuf::from(target.to_string_lossy().replace('*', matched))))
+            .collect();
+
+        let found = candidates.iter().find_map(|target| self.find_js_file(target));
+        let target = candidates.into_iter().next()?;
+        Some((target, found))
+    }
+
+    /// exact path, path with extension, or `index` file of directory
+    fn find_js_file(&self, target: &Path) -> Option<PathBuf> {
+        if self.files.contains_key(target) {
+            return Some(target.to_path_buf());
+        }
+
+        let with_ext = JS_EXTENSIONS.iter()
+            .map(|ext| PathBuf::from(format!("{}.{}", target.display(), ext)));
+        let index = JS_EXTENSIONS.iter()
+            .map(|ext| target.join(format!("index.{}", ext)));
+
+        with_ext.chain(index).find(|path| self.files.contains_key(path))
+    }
+
+    /// dotted package of a directory inside project root
+    pub fn path_package(&self, dir: &Path) -> String {
+        path_package(&self.root, dir)
+    }
+}
+
+/// `a.b` and `c` => `a.b.c`, either may be empty
+pub fn join_dotted(package: &str, name: &str) -> String {
+    if package.is_empty() {
+        name.to_string()
+    } else if name.is_empty() {
+        package.to_string()
+    } else {
+        format!("{}.{}", package, name)
+    }
+}
+
+/// `compilerOptions.paths` of `tsconfig.json` in `root`, targets are joined with `baseUrl`
+fn ts_paths(root: &Path) -> Vec<(String, Vec<PathBuf>)> {
+    let config = match fs::read_to_string(root.join("tsconfig.json")).ok().and_then(|content| parse_jsonc(&content)) {
+        Some(config) => config,
+        None => { return vec![]; }
+    };
+
+    let options = &config["compilerOptions"];
+    let base = normalize(&root.join(options["baseUrl"].as_str().unwrap_or(".")));
+    let paths = match options["paths"].as_object() {
+        Some(paths) => paths,
+        None => { return vec![]; }
+    };
+
+    paths.iter()
+        .map(|(alias, targets)| {
+            let targets = targets.as_array()
+                .map(|targets| targets.iter().filter_map(|t| t.as_str()).map(|t| base.join(t)).collect())
+                .unwrap_or_default();
+            (alias.clone(), targets)
+        })
+        .collect()
+}
+
+/// `tsconfig.json` allows comments and trailing commas
+fn parse_jsonc(content: &str) -> Option<Value> {
+    let chars: Vec<char> = content.chars().collect();
+    let mut json = String::with_capacity(content.len());
+    let mut in_string = false;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        if in_string {
+            json.push(c);
+            if c == '\\' {
+                if let Some(next) = next {
+                    json.push(next);
+                    i += 1;
+                }
+            } else if c == '"' {
+                in_string = false;
+            }
+        } else if c == '/' && next == Some('/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            continue;
+        } else if c == '/' && next == Some('*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i += 2;
+            continue;
+        } else if c == ',' && matches!(chars[i + 1..].iter().find(|c| !c.is_whitespace()), Some('}') | Some(']')) {
+            // trailing comma
+        } else {
+            in_string = c == '"';
+            json.push(c);
+        }
+        i += 1;
+    }
+
+    serde_json::from_str(&json).ok()
+}
+
+/// `root/src/app` => `src.app`
+pub fn path_package(root: &Path, dir: &Path) -> String {
+    let relative = dir.strip_prefix(root).unwrap_or(dir);
+    if relative.as_os_str().is_empty() {
+        return "".to_string();
+    }
+
+    PackageUnify::from_path(relative.to_path_buf())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Language {
+    Java,
+    Kotlin,
+    CSharp,
+    Rust,
+    JavaScript,
+    Go,
+    Python,
+    Cpp,
+    Other,
+}
+
+fn language_of(file_name: &str) -> Language {
+    let ext = Path::new(file_name).extension().and_then(|s| s.to_str()).unwrap_or("");
+    match ext {
+        "java" => Language::Java,
+        "kt" | "kts" => Language::Kotlin,
+        "cs" => Language::CSharp,
+        "rs" => Language::Rust,
+        "go" => Language::Go,
+        "py" => Language::Python,
+        _ if JS_EXTENSIONS.contains(&ext) => Language::JavaScript,
+        _ if CPP_EXTENSIONS.contains(&ext) => Language::Cpp,
+        _ => Language::Other,
+    }
+}
+
+/// languages which package is the directory of file: JavaScript, TypeScript, C and C++
+pub fn is_dir_package_file(file_name: &str) -> bool {
+    matches!(language_of(file_name), Language::JavaScript | Language::Cpp)
+}
+
+/// Crates of Rust files, found by the nearest `Cargo.toml` with a package name.
+///
+/// every directory is looked up once. directories inside `code_dir` are searched up to it,
+/// above it only the crate of `code_dir` itself is looked up, for scanning `src` of a crate.
+pub struct CrateRoots {
+    code_dir: PathBuf,
+    /// directory -> (crate name, crate dir)
+    dirs: HashMap<PathBuf, Option<(String, PathBuf)>>,
+}
+
+impl CrateRoots {
+    pub fn new(code_dir: &Path) -> CrateRoots {
+        CrateRoots {
+            code_dir: code_dir.to_path_buf(),
+            dirs: Default::default(),
+        }
+    }
+
+    /// crate name, crate dir and module path of a Rust file: `src/a/b.rs` and `src/a/b/mod.rs`
+    /// are module `a::b`; `src/lib.rs`, `src/main.rs`, and binaries like `src/bin/cli.rs` or
+    /// `src/bin/cli/main.rs` are the crate root.
+    pub fn module_path(&mut self, path: &Path) -> Option<(String, PathBuf, Vec<String>)> {
+        let (crate_name, crate_dir) = self.find(path.parent()?)?;
+        let module = module_path(&crate_dir, path);
+
+        Some((crate_name, crate_dir, module))
+    }
+
+    fn find(&mut self, dir: &Path) -> Option<(String, PathBuf)> {
+        if let Some(krate) = self.dirs.get(dir) {
+            return krate.clone();
+        }
+
+        let krate = match read_crate_name(dir) {
+            Some(name) => Some((name, dir.to_path_buf())),
+            None => match dir.parent() {
+                Some(parent) if dir != self.code_dir && dir.starts_with(&self.code_dir) => self.find(parent),
+                _ => dir.ancestors().skip(1)
+                    .find_map(|dir| read_crate_name(dir).map(|name| (name, dir.to_path_buf()))),
+            }
+        };
+
+        self.dirs.insert(dir.to_path_buf(), krate.clone());
+        krate
+    }
+}
+
+/// dotted import path of the package of a Go file: module path in nearest `go.mod`, with the
+/// directory of file inside module, like `github.com.inherd.app.domain`
+pub fn go_package(path: &Path) -> Option<String> {
+    let dir = path.parent()?;
+    for module_dir in dir.ancestors() {
+        if let Ok(content) = fs::read_to_string(module_dir.join("go.mod")) {
+            let module = go_module_name(&content)?;
+            let mut package = module.replace('/', ".");
+            let relative = dir.strip_prefix(module_dir).unwrap_or(dir);
+            if !relative.as_os_str().is_empty() {
+                package = join_dotted(&package, &PackageUnify::from_path(relative.to_path_buf()));
+            }
+            return Some(package);
+        }
+    }
+
+    None
+}
+
+/// dotted package of a Python file by directories with `__init__.py` above it, the first
+/// directory without it is the source root: `src/app/domain/models.py` is `app.domain`
+pub fn python_package(path: &Path) -> String {
+    let mut names = vec![];
+    let mut dir = path.parent();
+    while let Some(current) = dir {
+        let name = match current.file_name() {
+            Some(name) if current.join("__init__.py").is_file() => name,
+            _ => break
+        };
+
+        names.push(name.to_string_lossy().to_string());
+        dir = current.parent();
+    }
+
+    names.reverse();
+    names.join(".")
+}
+
+/// `module github.com/inherd/app` in `go.mod`
+pub fn go_module_name(go_mod: &str) -> Option<String> {
+    go_mod.lines()
+        .map(|line| line.trim())
+        .find(|line| line.starts_with("module "))
+        .map(|line| line["module ".len()..].trim().trim_matches('"').to_string())
+}
+
+fn module_path(crate_dir: &Path, path: &Path) -> Vec<String> {
+    let relative = path.strip_prefix(crate_dir).unwrap_or(path);
+    let relative = relative.strip_prefix("src").unwrap_or(relative);
+    let relative = match relative.strip_prefix("bin") {
+        Ok(bin) if bin.iter().count() == 1 => return vec![],
+        Ok(bin) => bin.iter().skip(1).collect(),
+        Err(_) => relative.to_path_buf(),
+    };
+
+    let mut module: Vec<String> = relative.iter()
+        .map(|s| s.to_string_lossy().to_string())
+        .collect();
+
+    if let Some(last) = module.pop() {
+        let stem = last.trim_end_matches(".rs");
+        let is_root = module.is_empty() && (stem == "lib" || stem == "main");
+        if stem != "mod" && !is_root {
+            module.push(stem.to_string());
+        }
+    }
+
+    module
+}
+
+/// package name in `Cargo.toml` of `dir`
+fn read_crate_name(dir: &Path) -> Option<String> {
+    let content = fs::read_to_string(dir.join("Cargo.toml")).ok()?;
+    crate_name(&content)
+}
+
+/// `name` in `[package]` section, `-` is replaced as in Rust code
+pub fn crate_name(manifest: &str) -> Option<String> {
+    let mut in_package = false;
+    for line in manifest.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_package = line == "[package]";
+            continue;
+        }
+
+        if in_package && line.starts_with("name") {
+            let value = line.split_once('=')?.1.trim().trim_matches('"');
+            return Some(value.replace('-', "_"));
+        }
+    }
+
+    None
+}
+
+/// remove `.` and `..` without touching file system
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                match normalized.components().next_back() {
+                    Some(Component::Normal(_)) => { normalized.pop(); }
+                    _ => normalized.push(".."),
+                }
+            }
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use guarding_core::domain::code_file::CodeFile;
+    use guarding_core::domain::code_import::CodeImport;
+
+    use crate::import_resolver::{crate_name, go_module_name, go_package, python_package, CrateRoots, ImportResolver};
+
+    /// empty dir of a test, named by process id so that parallel runs don't share it
+    fn temp_root(test: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("guarding-resolver-{}-{}", test, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        root
+    }
+
+    fn code_file(path: &str, package: &str, imports: Vec<&str>) -> CodeFile {
+        CodeFile {
+            path: path.to_string(),
+            file_name: Path::new(path).file_name().unwrap().to_str().unwrap().to_string(),
+            package: package.to_string(),
+            imports: imports.iter().map(|s| s.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn should_resolve_java_imports() {
+        let mut models = vec![
+            code_file("src/com/phodal/app/App.java", "com.phodal.app", vec!["com.phodal.domain.User", "com.phodal.domain", "java.util.Map"]),
+            code_file("src/com/phodal/domain/User.java", "com.phodal.domain", vec![]),
+        ];
+
+        ImportResolver::resolve_models(Path::new("src"), &mut models);
+        let deps = &models[0].dependencies;
+
+        assert_eq!("com.phodal.domain", deps[0].package);
+        assert_eq!("com.phodal.domain.User", deps[0].name);
+        assert_eq!(Some("src/com/phodal/domain/User.java".to_string()), deps[0].file);
+
+        assert_eq!("com.phodal.domain", deps[1].package);
+        assert_eq!(None, deps[1].file);
+
+        assert_eq!("java.util", deps[2].package);
+        assert_eq!(None, deps[2].file);
+    }
+
+    #[test]
+    fn should_resolve_java_static_and_wildcard_imports() {
+        let mut app = code_file("src/com/phodal/app/App.java", "com.phodal.app", vec!["com.phodal.domain.*", "com.phodal.domain.User.create", "com.phodal.domain.User.*"]);
+        for import in app.imports.clone() {
+            app.code_imports.push(CodeImport {
+                is_static: import.contains("User"),
+                is_wildcard: import.ends_with(".*"),
+                import,
+                ..Default::default()
+            });
+        }
+
+        let mut models = vec![app, code_file("src/com/phodal/domain/User.java", "com.phodal.domain", vec![])];
+        ImportResolver::resolve_models(Path::new("src"), &mut models);
+        let deps = &models[0].dependencies;
+
+        assert_eq!("com.phodal.domain", deps[0].package);
+        assert!(deps[0].is_wildcard);
+
+        for dep in &deps[1..] {
+            assert_eq!("com.phodal.domain.User", dep.name);
+            assert_eq!(Some("src/com/phodal/domain/User.java".to_string()), dep.file);
+            assert!(!dep.is_wildcard);
+        }
+        assert_eq!("com.phodal.domain.User.*", deps[2].source);
+    }
+
+    #[test]
+    fn should_resolve_kotlin_imports_of_java() {
+        let mut models = vec![
+            code_file("src/com/phodal/app/Api.kt", "com.phodal.app", vec!["com.phodal.domain.User"]),
+            code_file("src/com/phodal/domain/User.java", "com.phodal.domain", vec![]),
+        ];
+
+        ImportResolver::resolve_models(Path::new("src"), &mut models);
+        let deps = &models[0].dependencies;
+
+        assert_eq!("com.phodal.domain", deps[0].package);
+        assert_eq!(Some("src/com/phodal/domain/User.java".to_string()), deps[0].file);
+    }
+
+    #[test]
+    fn should_resolve_js_relative_imports() {
+        let mut models = vec![
+            code_file("web/src/app/main.js", "", vec!["'./say.js'", "'../lib/db'", "'../components'", "'react'", "'@scope/pkg/sub'"]),
+            code_file("web/src/app/say.js", "", vec![]),
+            code_file("web/src/lib/db.js", "", vec![]),
+            code_file("web/src/components/index.jsx", "", vec![]),
+        ];
+
+        ImportResolver::resolve_models(Path::new("web"), &mut models);
+        let deps = &models[0].dependencies;
+
+        assert_eq!("src.app", deps[0].package);
+        assert_eq!("src.app.say", deps[0].name);
+        assert_eq!(Some("web/src/app/say.js".to_string()), deps[0].file);
+
+        assert_eq!("src.lib", deps[1].package);
+        assert_eq!(Some("web/src/lib/db.js".to_string()), deps[1].file);
+
+        assert_eq!("src.components", deps[2].package);
+        assert_eq!(Some("web/src/components/index.jsx".to_string()), deps[2].file);
+
+        assert_eq!("react", deps[3].package);
+        assert_eq!("scope.pkg.sub", deps[4].package);
+        assert_eq!(None, deps[4].file);
+    }
+
+    #[test]
+    fn should_resolve_ts_path_alias() {
+        let root = temp_root("should_resolve_ts_path_alias");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("tsconfig.json"), "{
+  // path alias of app
+  \"compilerOptions\": {
+    \"baseUrl\": \"./src\",
+    \"paths\": {
+      \"@app/*\": [\"app/*\"],
+      \"@app/shared/*\": [\"shared/*\"],
+      \"@env\": [\"environments/env.ts\"],
+    },
+  },
+}").unwrap();
+
+        let path = |p: &str| root.join(p).display().to_string();
+        let mut models = vec![
+            code_file(&path("src/main.ts"), "", vec!["'@app/domain/user'", "'@app/shared/log'", "'@env'", "'@angular/core'"]),
+            code_file(&path("src/app/domain/user.ts"), "", vec![]),
+            code_file(&path("src/shared/log.ts"), "", vec![]),
+        ];
+
+        ImportResolver::resolve_models(&root, &mut models);
+        let deps = &models[0].dependencies;
+
+        assert_eq!("src.app.domain", deps[0].package);
+        assert_eq!("src.app.domain.user", deps[0].name);
+        assert_eq!(Some(path("src/app/domain/user.ts")), deps[0].file);
+
+        assert_eq!("src.shared", deps[1].package);
+        assert_eq!(Some(path("src/shared/log.ts")), deps[1].file);
+
+        assert_eq!("src.environments", deps[2].package);
+        assert_eq!(None, deps[2].file);
+
+        assert_eq!("angular.core", deps[3].package);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn should_resolve_rust_imports() {
+        let root = temp_root("should_resolve_rust_imports");
+        fs::create_dir_all(root.join("src").join("domain")).unwrap();
+        fs::write(root.join("Cargo.toml"), "[package]\nname = \"my-app\"\n").unwrap();
+
+        let path = |p: &str| root.join(p).display().to_string();
+        let mut models = vec![
+            code_file(&path("src/lib.rs"), "", vec!["crate::domain::user::User", "std::path::PathBuf"]),
+            code_file(&path("src/domain/mod.rs"), "", vec!["self::user::User", "self::*", "std::collections::*"]),
+            code_file(&path("src/domain/user.rs"), "", vec!["super::Repository", "my_app::domain::Repository"]),
+        ];
+
+        ImportResolver::resolve_models(&root, &mut models);
+
+        let deps = &models[0].dependencies;
+        assert_eq!("my_app.domain.user.User", deps[0].name);
+        assert_eq!("my_app.domain.user", deps[0].package);
+        assert_eq!(Some(path("src/domain/user.rs")), deps[0].file);
+        assert_eq!("std.path", deps[1].package);
+        assert_eq!(None, deps[1].file);
+
+        assert_eq!("my_app.domain.user", models[1].dependencies[0].package);
+        assert_eq!("my_app.domain", models[1].dependencies[1].package);
+        assert!(models[1].dependencies[1].is_wildcard);
+        assert_eq!("std.collections", models[1].dependencies[2].package);
+        assert!(models[1].dependencies[2].is_wildcard);
+
+        let deps = &models[2].dependencies;
+        assert_eq!("my_app.domain", deps[0].package);
+        assert_eq!(Some(path("src/domain/mod.rs")), deps[0].file);
+        assert_eq!(deps[0].package, deps[1].package);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn should_resolve_rust_use_in_inline_module() {
+        let root = temp_root("should_resolve_rust_use_in_inline_module");
+        fs::create_dir_all(root.join("src").join("a")).unwrap();
+        fs::write(root.join("Cargo.toml"), "[package]\nname = \"my-app\"\n").unwrap();
+
+        let path = |p: &str| root.join(p).display().to_string();
+        let mut b = code_file(&path("src/a/b.rs"), "", vec!["super::Repository", "super::*"]);
+        b.code_imports = vec![
+            CodeImport { import: "super::Repository".to_string(), ..Default::default() },
+            CodeImport { import: "super::*".to_string(), is_wildcard: true, module: "tests".to_string(), ..Default::default() },
+        ];
+        let mut models = vec![code_file(&path("src/a/mod.rs"), "", vec![]), b];
+
+        ImportResolver::resolve_models(&root, &mut models);
+
+        let deps = &models[1].dependencies;
+        assert_eq!("my_app.a", deps[0].package);
+        assert_eq!("my_app.a.b", deps[1].package);
+        assert_eq!(Some(path("src/a/b.rs")), deps[1].file);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn should_resolve_go_package_by_module() {
+        let root = temp_root("should_resolve_go_package_by_module");
+        fs::create_dir_all(root.join("internal").join("domain")).unwrap();
+        fs::write(root.join("go.mod"), "module github.com/inherd/app\n\ngo 1.16\n").unwrap();
+
+        assert_eq!(Some("github.com.inherd.app".to_string()), go_package(&root.join("main.go")));
+        assert_eq!(Some("github.com.inherd.app.internal.domain".to_string()), go_package(&root.join("internal/domain/user.go")));
+        assert_eq!(Some("example.com/x".to_string()), go_module_name("// comment\nmodule \"example.com/x\"\n"));
+
+        let mut models = vec![code_file("cmd/main.go", "main", vec!["github.com/inherd/app/internal/domain"])];
+        ImportResolver::resolve_models(Path::new("cmd"), &mut models);
+        assert_eq!("github.com.inherd.app.internal.domain", models[0].dependencies[0].package);
+        assert_eq!(None, models[0].dependencies[0].file);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn should_resolve_python_imports() {
+        let root = temp_root("should_resolve_python_imports");
+        fs::create_dir_all(root.join("src").join("app").join("domain")).unwrap();
+        fs::create_dir_all(root.join("src").join("app").join("api")).unwrap();
+        for dir in &["src/app", "src/app/domain", "src/app/api"] {
+            fs::write(root.join(dir).join("__init__.py"), "").unwrap();
+        }
+
+        let path = |p: &str| root.join(p).display().to_string();
+        assert_eq!("app.api", python_package(&root.join("src/app/api/views.py")));
+        assert_eq!("", python_package(&root.join("src/manage.py")));
+
+        let mut models = vec![
+            code_file(&path("src/app/api/views.py"), "app.api", vec!["..domain.models.User", ".serializers", "app.domain", "django.db.models"]),
+            code_file(&path("src/app/api/serializers.py"), "app.api", vec![]),
+            code_file(&path("src/app/domain/models.py"), "app.domain", vec![]),
+            code_file(&path("src/app/domain/__init__.py"), "app.domain", vec![]),
+        ];
+
+        ImportResolver::resolve_models(&root, &mut models);
+        let deps = &models[0].dependencies;
+
+        assert_eq!("app.domain.models.User", deps[0].name);
+        assert_eq!("app.domain", deps[0].package);
+        assert_eq!(Some(path("src/app/domain/models.py")), deps[0].file);
+
+        assert_eq!("app.api.serializers", deps[1].name);
+        assert_eq!(Some(path("src/app/api/serializers.py")), deps[1].file);
+
+        assert_eq!("app.domain", deps[2].package);
+        assert_eq!(Some(path("src/app/domain/__init__.py")), deps[2].file);
+
+        assert_eq!("django.db", deps[3].package);
+        assert_eq!(None, deps[3].file);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn should_resolve_cpp_includes() {
+        let mut models = vec![
+            code_file("firmware/src/main.c", "src", vec!["drivers/uart.h", "config.h", "stdio.h", "sys/types.h"]),
+            code_file("firmware/src/drivers/uart.h", "src.drivers", vec![]),
+            code_file("firmware/config.h", "", vec![]),
+            code_file("firmware/src/app/user.hpp", "app.domain", vec![]),
+            code_file("firmware/src/app/service.cpp", "app", vec!["user.hpp"]),
+        ];
+
+        ImportResolver::resolve_models(Path::new("firmware"), &mut models);
+        let deps = &models[0].dependencies;
+
+        assert_eq!("src.drivers.uart", deps[0].name);
+        assert_eq!("src.drivers", deps[0].package);
+        assert_eq!(Some("firmware/src/drivers/uart.h".to_string()), deps[0].file);
+
+        assert_eq!(Some("firmware/config.h".to_string()), deps[1].file);
+
+        assert_eq!("stdio", deps[2].package);
+        assert_eq!(None, deps[2].file);
+        assert_eq!("sys.types", deps[3].name);
+        assert_eq!("sys", deps[3].package);
+
+        assert_eq!("app.domain", models[4].dependencies[0].package);
+    }
+
+    #[test]
+    fn should_find_crate_inside_and_of_code_dir() {
+        let root = temp_root("should_find_crate_inside_and_of_code_dir");
+        fs::create_dir_all(root.join("src/domain")).unwrap();
+        fs::create_dir_all(root.join("src/plugin/src")).unwrap();
+        fs::write(root.join("Cargo.toml"), "[package]\nname = \"my-app\"\n").unwrap();
+        fs::write(root.join("src/plugin/Cargo.toml"), "[package]\nname = \"plugin\"\n").unwrap();
+
+        let code_dir = root.join("src");
+        let mut crate_roots = CrateRoots::new(&code_dir);
+
+        let (name, dir, module) = crate_roots.module_path(&code_dir.join("domain/user.rs")).unwrap();
+        assert_eq!(("my_app", root.clone(), vec!["domain".to_string(), "user".to_string()]), (name.as_str(), dir, module));
+
+        let (name, _, module) = crate_roots.module_path(&code_dir.join("plugin/src/lib.rs")).unwrap();
+        assert_eq!(("plugin", vec![]), (name.as_str(), module));
+
+        let (_, _, module) = crate_roots.module_path(&code_dir.join("bin/cli.rs")).unwrap();
+        assert!(module.is_empty());
+        let (_, _, module) = crate_roots.module_path(&code_dir.join("bin/cli/main.rs")).unwrap();
+        assert!(module.is_empty());
+        let (_, _, module) = crate_roots.module_path(&code_dir.join("bin/cli/args.rs")).unwrap();
+        assert_eq!(vec!["args".to_string()], module);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn should_read_crate_name() {
+        let manifest = "[package]\nname = \"guarding-ident\"\n\n[dependencies]\nname = \"x\"";
+        assert_eq!(Some("guarding_ident".to_string()), crate_name(manifest));
+        assert_eq!(None, crate_name("[workspace]\nmembers = []"));
+    }
+}
@@ -1,10 +1,17 @@
 pub mod identify;
 pub mod model_builder;
+pub mod import_resolver;
 
-pub use model_builder::ModelBuilder;
+pub use model_builder::{ModelBuilder, ModelStatistics};
+pub use import_resolver::ImportResolver;
 
 pub use identify::code_ident;
 pub use identify::java_ident;
+pub use identify::kotlin_ident;
 pub use identify::js_ident;
+pub use identify::ts_ident;
 pub use identify::rust_ident;
+pub use identify::go_ident;
+pub use identify::cpp_ident;
+pub use identify::python_ident;
 pub use identify::c_sharp_ident;
@@ -1,21 +1,71 @@
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::Instant;
 
+use serde::{Deserialize, Serialize};
 use walkdir::WalkDir;
 
 use guarding_core::domain::code_file::CodeFile;
+use crate::import_resolver::{self, CrateRoots, ImportResolver};
 use crate::identify::c_sharp_ident::CSharpIdent;
 use crate::identify::code_ident::CodeIdent;
+use crate::identify::cpp_ident::CppIdent;
+use crate::identify::go_ident::GoIdent;
 use crate::identify::java_ident::JavaIdent;
+use crate::identify::python_ident::PythonIdent;
 use crate::identify::js_ident::JsIdent;
+use crate::identify::kotlin_ident::KotlinIdent;
 use crate::identify::rust_ident::RustIdent;
+use crate::identify::ts_ident::TsIdent;
 
 pub struct ModelBuilder {}
 
+/// What `ModelBuilder` parsed from source code.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ModelStatistics {
+    pub files: usize,
+    pub classes: usize,
+    /// functions and methods
+    pub functions: usize,
+    pub lines: usize,
+    pub loc: usize,
+    /// count of files by extension
+    pub languages: BTreeMap<String, usize>,
+    pub elapsed_ms: f64,
+}
+
+impl ModelStatistics {
+    pub fn from_models(models: &[CodeFile], elapsed_ms: f64) -> ModelStatistics {
+        let mut statistics = ModelStatistics {
+            files: models.len(),
+            elapsed_ms,
+            ..Default::default()
+        };
+
+        for file in models {
+            statistics.classes += file.classes.len();
+            statistics.functions += file.functions.len();
+            statistics.functions += file.classes.iter().map(|class| class.functions.len()).sum::<usize>();
+            statistics.lines += file.lines;
+            statistics.loc += file.loc;
+
+            let ext = Path::new(&file.file_name).extension()
+                .and_then(|ext| ext.to_str())
+                .unwrap_or("");
+            *statistics.languages.entry(ext.to_string()).or_insert(0) += 1;
+        }
+
+        statistics
+    }
+}
+
 impl ModelBuilder {
+    /// parse all files in `code_dir`, then resolve their imports
     pub fn build_models_by_dir(code_dir: PathBuf) -> Vec<CodeFile> {
         let mut models = vec![];
-        for entry in WalkDir::new(code_dir) {
+        let mut crate_roots = CrateRoots::new(&code_dir);
+        for entry in WalkDir::new(&code_dir) {
             let entry = entry.unwrap();
             if !entry.file_type().is_file() {
                 continue;
@@ -26,57 +76,117 @@ impl ModelBuilder {
                 continue;
             }
 
-            ModelBuilder::build_model_by_file(&mut models, path)
+            ModelBuilder::build_model(&mut models, path, &mut crate_roots)
         }
+
+        ModelBuilder::insert_dir_packages(&code_dir, &mut models);
+        ImportResolver::resolve_models(&code_dir, &mut models);
         models
     }
 
+    /// same as `build_models_by_dir`, with statistics of models
+    pub fn build_models_with_statistics(code_dir: PathBuf) -> (Vec<CodeFile>, ModelStatistics) {
+        let start = Instant::now();
+        let models = ModelBuilder::build_models_by_dir(code_dir);
+        let statistics = ModelStatistics::from_models(&models, start.elapsed().as_secs_f64() * 1000.0);
+
+        (models, statistics)
+    }
+
     pub fn build_model_by_file(models: &mut Vec<CodeFile>, path: &Path) {
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        ModelBuilder::build_model(models, path, &mut CrateRoots::new(dir))
+    }
+
+    fn build_model(models: &mut Vec<CodeFile>, path: &Path, crate_roots: &mut CrateRoots) {
         let ext = path.extension().unwrap().to_str().unwrap();
         let file_name = path.file_name().unwrap().to_str().unwrap();
 
+        let parse: fn(&str) -> CodeFile = match ext {
+            "java" => JavaIdent::parse,
+            "kt" | "kts" => KotlinIdent::parse,
+            "js" | "jsx" | "mjs" | "cjs" => JsIdent::parse,
+            "ts" | "mts" | "cts" => TsIdent::parse,
+            "tsx" => TsIdent::parse_tsx,
+            "rs" => RustIdent::parse,
+            "go" => GoIdent::parse,
+            "py" => PythonIdent::parse,
+            "c" | "h" | "cpp" | "hpp" | "cc" => CppIdent::parse,
+            "cs" => CSharpIdent::parse,
+            &_ => { return; }
+        };
+
+        let content = match ModelBuilder::read_content(path) {
+            Some(content) => content,
+            None => { return; }
+        };
+
+        let mut file = parse(content.as_str());
         match ext {
-            "java" => {
-                let mut file = JavaIdent::parse(ModelBuilder::read_content(path).as_str());
-                file.path = ModelBuilder::format_path(path);
-                file.file_name = file_name.to_string();
-                models.push(file);
-            }
-            "js" => {
-                let mut file = JsIdent::parse(ModelBuilder::read_content(path).as_str());
-                file.path = format!("{}", path.display());
-                file.file_name = file_name.to_string();
-                models.push(file);
-            }
             "rs" => {
-                let mut file = RustIdent::parse(ModelBuilder::read_content(path).as_str());
-                file.path = format!("{}", path.display());
-                file.file_name = file_name.to_string();
-                models.push(file);
+                if let Some((crate_name, _, module)) = crate_roots.module_path(path) {
+                    let mut package = vec![crate_name];
+                    package.extend(module);
+                    file.package = package.join(".");
+                }
+                for class in file.classes.iter_mut() {
+                    class.package = import_resolver::join_dotted(&file.package, &class.package);
+                }
+            }
+            "go" => {
+                if let Some(package) = import_resolver::go_package(path) {
+                    file.package = package;
+                    for class in file.classes.iter_mut() {
+                        class.package = file.package.clone();
+                    }
+                }
             }
-            "cs" => {
-                let mut file = CSharpIdent::parse(ModelBuilder::read_content(path).as_str());
-                file.path = format!("{}", path.display());
-                file.file_name = file_name.to_string();
-                models.push(file);
+            "py" => {
+                file.package = import_resolver::python_package(path);
+                for class in file.classes.iter_mut() {
+                    class.package = file.package.clone();
+                }
             }
             &_ => {}
         }
+
+        file.path = format!("{}", path.display());
+        file.file_name = file_name.to_string();
+        models.push(file);
     }
 
-    fn read_content(path: &Path) -> String {
-        fs::read_to_string(path).expect("not such file")
+    /// JavaScript, TypeScript, C, and C++ without namespace, use their directory inside
+    /// `code_dir` as package, like `src.app.routes`
+    fn insert_dir_packages(code_dir: &Path, models: &mut [CodeFile]) {
+        for file in models.iter_mut() {
+            if !file.package.is_empty() || !import_resolver::is_dir_package_file(&file.file_name) {
+                continue;
+            }
+
+            let dir = Path::new(&file.path).parent().unwrap_or_else(|| Path::new(""));
+            file.package = import_resolver::path_package(code_dir, dir);
+            for class in file.classes.iter_mut().filter(|class| class.package.is_empty()) {
+                class.package = file.package.clone();
+            }
+        }
     }
 
-    fn format_path(path: &Path) -> String {
-        format!("{}", path.display())
+    /// content of file, invalid UTF-8 is replaced, unreadable files are skipped with a warning
+    fn read_content(path: &Path) -> Option<String> {
+        match fs::read(path) {
+            Ok(bytes) => Some(String::from_utf8_lossy(&bytes).into_owned()),
+            Err(e) => {
+                println!("skip {}: {}", path.display(), e);
+                None
+            }
+        }
     }
 }
 
 
 #[cfg(test)]
 mod tests {
-    use std::env;
+    use std::{env, fs};
     use crate::ModelBuilder;
 
     #[test]
@@ -86,4 +196,51 @@ mod tests {
 
         assert!(models.len() > 0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn should_use_module_path_as_rust_package() {
+        let dir = env::current_dir().unwrap().join("src");
+        let models = ModelBuilder::build_models_by_dir(dir);
+
+        let builder = models.iter().find(|file| file.file_name == "model_builder.rs").unwrap();
+        assert_eq!("guarding_ident.model_builder", builder.package);
+        let class = builder.classes.iter().find(|class| class.name == "ModelBuilder").unwrap();
+        assert_eq!("guarding_ident.model_builder", class.package);
+
+        let rust_ident = models.iter().find(|file| file.file_name == "rust_ident.rs").unwrap();
+        assert_eq!("guarding_ident.identify.rust_ident", rust_ident.package);
+    }
+
+    #[test]
+    fn should_parse_file_with_invalid_utf8() {
+        let root = env::temp_dir().join(format!("guarding-model-should_parse_file_with_invalid_utf8-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("Latin.java"), b"// caf\xe9\nclass Latin {}\n").unwrap();
+
+        let models = ModelBuilder::build_models_by_dir(root.clone());
+        assert_eq!("Latin", models[0].classes[0].name);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn should_use_dir_as_js_package() {
+        let root = env::temp_dir().join(format!("guarding-model-should_use_dir_as_js_package-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("src").join("app")).unwrap();
+        fs::create_dir_all(root.join("src").join("db")).unwrap();
+        fs::write(root.join("src/app/routes.cjs"), "const db = require('../db');\nmodule.exports = () => import('./lazy.js');\n").unwrap();
+        fs::write(root.join("src/db/index.js"), "module.exports = {};\n").unwrap();
+
+        let models = ModelBuilder::build_models_by_dir(root.clone());
+        let routes = models.iter().find(|file| file.file_name == "routes.cjs").unwrap();
+
+        assert_eq!("src.app", routes.package);
+        assert_eq!("src.db", routes.dependencies[0].package);
+        assert_eq!(Some(root.join("src/db/index.js").display().to_string()), routes.dependencies[0].file);
+        assert_eq!("src.app.lazy", routes.dependencies[1].name);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -1,6 +1,8 @@
+use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::fmt;
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GuardRule {
     pub origin: String,
     pub ty: RuleType,
@@ -8,7 +10,9 @@ pub struct GuardRule {
     pub scope: RuleScope,
     pub expr: Expr,
     pub ops: Vec<Operator>,
-    pub assert: RuleAssert
+    pub assert: RuleAssert,
+    /// `failOnEmpty` or `allowEmpty` of the rule, `None` uses the global option
+    pub fail_on_empty: Option<bool>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -37,21 +41,22 @@ impl Default for GuardRule {
             scope: RuleScope::All,
             expr: Expr::Identifier("".to_string()),
             ops: vec![],
-            assert: RuleAssert::Empty
+            assert: RuleAssert::Empty,
+            fail_on_empty: None,
         }
     }
 }
 
 impl GuardRule {
-    pub fn assert_sized(rule: &GuardRule) -> usize {
-        let mut size = 0;
+    pub fn assert_number(rule: &GuardRule) -> Number {
+        let mut number = Number::Integer(0);
         match &rule.assert {
-            RuleAssert::Sized(sized) => {
-                size = *sized;
+            RuleAssert::Number(num) => {
+                number = *num;
             }
             _ => {}
         }
-        size
+        number
     }
 
     pub fn assert_string(rule: &GuardRule) -> String {
@@ -88,12 +93,13 @@ pub enum RuleType {
     Layer,
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub enum RuleLevel {
     Package,
     Function,
     Class,
     Struct,
+    File,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -139,7 +145,7 @@ impl Default for FunctionCall {
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub enum Operator {
     /// >
     Gt,
@@ -170,14 +176,132 @@ pub enum Operator {
     Inside,
     ResideIn,
     Accessed,
-    DependBy
+    DependBy,
+
+    // naming operator, `should be PascalCase`
+    Be,
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum RuleAssert {
     Empty,
     Stringed(String),
     Leveled(RuleLevel, String),
     ArrayStringed(Vec<String>),
-    Sized(usize),
+    Number(Number),
+    Naming(NamingStyle),
+}
+
+/// Casing convention of names, acronyms and digits are allowed, like `HTTPServer2`
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum NamingStyle {
+    /// `JsonParser`
+    PascalCase,
+    /// `parseJson`, leading underscores are ignored
+    CamelCase,
+    /// `parse_json`, leading underscores are ignored
+    SnakeCase,
+    /// `MAX_SIZE`
+    ScreamingSnake,
+}
+
+impl NamingStyle {
+    pub fn is_match(&self, name: &str) -> bool {
+        match self {
+            NamingStyle::PascalCase => {
+                starts_with(name, |c| c.is_ascii_uppercase()) && name.chars().all(|c| c.is_ascii_alphanumeric())
+            }
+            NamingStyle::CamelCase => {
+                let name = name.trim_start_matches('_');
+                starts_with(name, |c| c.is_ascii_lowercase()) && name.chars().all(|c| c.is_ascii_alphanumeric())
+            }
+            NamingStyle::SnakeCase => {
+                let name = name.trim_start_matches('_');
+                starts_with(name, |c| c.is_ascii_lowercase())
+                    && is_snake(name, |c| c.is_ascii_lowercase() || c.is_ascii_digit())
+            }
+            NamingStyle::ScreamingSnake => {
+                starts_with(name, |c| c.is_ascii_uppercase())
+                    && is_snake(name, |c| c.is_ascii_uppercase() || c.is_ascii_digit())
+            }
+        }
+    }
+}
+
+fn starts_with(name: &str, predicate: fn(char) -> bool) -> bool {
+    matches!(name.chars().next(), Some(c) if predicate(c))
+}
+
+/// words of `is_word_char` joined by single underscores
+fn is_snake(name: &str, is_word_char: fn(char) -> bool) -> bool {
+    name.split('_').all(|word| !word.is_empty() && word.chars().all(is_word_char))
+}
+
+impl fmt::Display for NamingStyle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NamingStyle::PascalCase => write!(f, "PascalCase"),
+            NamingStyle::CamelCase => write!(f, "camelCase"),
+            NamingStyle::SnakeCase => write!(f, "snake_case"),
+            NamingStyle::ScreamingSnake => write!(f, "SCREAMING_SNAKE"),
+        }
+    }
+}
+
+/// A numeric assert value: `20`, `-1`, `0.3` or `80%`, compared by value, so `1` equals `1.0`
+/// and `80%` equals `0.8`
+#[derive(Clone, Copy, Debug)]
+pub enum Number {
+    Integer(i64),
+    Decimal(f64),
+    /// keep the written value, `80%` is `Percentage(80.0)`
+    Percentage(f64),
+}
+
+impl Number {
+    /// the value used for comparison, percentages are converted to ratios: `80%` is `0.8`
+    pub fn as_f64(&self) -> f64 {
+        match *self {
+            Number::Integer(int) => int as f64,
+            Number::Decimal(decimal) => decimal,
+            Number::Percentage(percent) => percent / 100.0,
+        }
+    }
+}
+
+impl From<usize> for Number {
+    fn from(size: usize) -> Self {
+        Number::Integer(size as i64)
+    }
+}
+
+impl From<f64> for Number {
+    fn from(decimal: f64) -> Self {
+        Number::Decimal(decimal)
+    }
+}
+
+impl PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Number::Integer(a), Number::Integer(b)) => a.partial_cmp(b),
+            _ => self.as_f64().partial_cmp(&other.as_f64()),
+        }
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Number::Integer(int) => write!(f, "{}", int),
+            Number::Decimal(decimal) => write!(f, "{}", decimal),
+            Number::Percentage(percent) => write!(f, "{}%", percent),
+        }
+    }
 }
\ No newline at end of file
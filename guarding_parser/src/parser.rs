@@ -2,7 +2,7 @@ use pest::iterators::{Pair, Pairs};
 use pest::Parser;
 
 use crate::errors::{Error, Result as GuardingResult};
-use crate::ast::{Expr, GuardRule, Operator, RuleAssert, RuleLevel, RuleScope};
+use crate::ast::{Expr, GuardRule, NamingStyle, Number, Operator, RuleAssert, RuleLevel, RuleScope};
 use crate::support::str_support;
 
 #[derive(Parser)]
@@ -58,7 +58,10 @@ fn parse_layer_rule(_pair: Pair<Rule>) -> GuardRule {
 }
 
 fn parse_normal_rule(pair: Pair<Rule>) -> GuardRule {
-    let mut guard_rule = GuardRule::default();
+    let mut guard_rule = GuardRule {
+        origin: pair.as_str().to_string(),
+        ..Default::default()
+    };
 
     for p in pair.into_inner() {
         match p.as_rule() {
@@ -77,6 +80,9 @@ fn parse_normal_rule(pair: Pair<Rule>) -> GuardRule {
             Rule::assert => {
                 guard_rule.assert = parse_assert(p);
             }
+            Rule::empty_option => {
+                guard_rule.fail_on_empty = Some(p.as_str() == "failOnEmpty");
+            }
             Rule::scope => {
                 guard_rule.scope = parse_scope(p);
             }
@@ -102,6 +108,7 @@ fn parse_rule_level(pair: Pair<Rule>) -> RuleLevel {
         "function" => { RuleLevel::Function }
         "class" => { RuleLevel::Class }
         "struct" => { RuleLevel::Struct }
+        "file" => { RuleLevel::File }
         &_ => { unreachable!("error rule level: {:?}", level_str) }
     }
 }
@@ -136,6 +143,7 @@ fn parse_operator(parent: Pair<Rule>) -> Vec<Operator> {
         Rule::op_resideIn => { Operator::ResideIn }
         Rule::op_accessed => { Operator::Accessed }
         Rule::op_dependBy => { Operator::DependBy }
+        Rule::op_be => { Operator::Be }
         _ => {
             panic!("implementing ops: {:?}, text: {:?}", pair.as_rule(), pair.as_span())
         }
@@ -194,14 +202,21 @@ fn parse_assert(parent: Pair<Rule>) -> RuleAssert {
 
             RuleAssert::Leveled(level, str)
         }
-        Rule::sized => {
+        Rule::naming => {
+            let style = match pair.as_str() {
+                "PascalCase" => NamingStyle::PascalCase,
+                "camelCase" => NamingStyle::CamelCase,
+                "snake_case" => NamingStyle::SnakeCase,
+                _ => NamingStyle::ScreamingSnake,
+            };
+
+            RuleAssert::Naming(style)
+        }
+        Rule::numbered => {
             let mut pairs = pair.into_inner();
             let pair = pairs.next().unwrap();
-            let size: usize = pair.as_str()
-                .parse()
-                .expect("convert int error");
 
-            RuleAssert::Sized(size)
+            RuleAssert::Number(parse_number(pair))
         }
         Rule::stringed => {
             let mut pairs = pair.into_inner();
@@ -228,6 +243,26 @@ fn parse_assert(parent: Pair<Rule>) -> RuleAssert {
     }
 }
 
+fn parse_number(pair: Pair<Rule>) -> Number {
+    let text = pair.as_str();
+    match pair.as_rule() {
+        Rule::percentage => {
+            let percent = text.trim_end_matches('%');
+            Number::Percentage(percent.parse().expect("convert percentage error"))
+        }
+        Rule::decimal => {
+            Number::Decimal(text.parse().expect("convert decimal error"))
+        }
+        _ => {
+            // out of i64 range, still can compare as decimal
+            match text.parse::<i64>() {
+                Ok(int) => Number::Integer(int),
+                Err(_) => Number::Decimal(text.parse().expect("convert int error"))
+            }
+        }
+    }
+}
+
 fn parse_scope(parent: Pair<Rule>) -> RuleScope {
     let mut pairs = parent.into_inner();
     let pair = pairs.next().unwrap();
@@ -277,7 +312,7 @@ fn string_from_pair(pair: Pair<Rule>) -> String {
 
 #[cfg(test)]
 mod tests {
-    use crate::ast::{Expr, Operator, RuleAssert, RuleLevel, RuleScope};
+    use crate::ast::{Expr, NamingStyle, Number, Operator, RuleAssert, RuleLevel, RuleScope};
     use crate::parser::parse;
 
     #[test]
@@ -291,6 +326,73 @@ mod tests {
         assert_eq!(RuleAssert::Stringed("Controller".to_string()), rules[0].assert);
     }
 
+    #[test]
+    fn should_parse_naming_style() {
+        let code = "class(\".\")::name should be PascalCase;
+function(\".\")::name should not be snake_case;
+class(\".\")::constant.name should be SCREAMING_SNAKE;";
+        let rules = parse(code).unwrap();
+
+        assert_eq!(vec![Operator::Be], rules[0].ops);
+        assert_eq!(RuleAssert::Naming(NamingStyle::PascalCase), rules[0].assert);
+        assert_eq!(vec![Operator::Not, Operator::Be], rules[1].ops);
+        assert_eq!(RuleAssert::Naming(NamingStyle::SnakeCase), rules[1].assert);
+        assert_eq!(RuleAssert::Naming(NamingStyle::ScreamingSnake), rules[2].assert);
+    }
+
+    #[test]
+    fn should_match_naming_style() {
+        assert!(NamingStyle::PascalCase.is_match("JsonParser"));
+        assert!(NamingStyle::PascalCase.is_match("HTTPServer2"));
+        assert!(!NamingStyle::PascalCase.is_match("jsonParser"));
+        assert!(!NamingStyle::PascalCase.is_match("Json_Parser"));
+
+        assert!(NamingStyle::CamelCase.is_match("parseXML"));
+        assert!(NamingStyle::CamelCase.is_match("_private"));
+        assert!(!NamingStyle::CamelCase.is_match("ParseXml"));
+
+        assert!(NamingStyle::SnakeCase.is_match("parse_v2_json"));
+        assert!(NamingStyle::SnakeCase.is_match("_unused"));
+        assert!(!NamingStyle::SnakeCase.is_match("parse__json"));
+        assert!(!NamingStyle::SnakeCase.is_match("parseJson"));
+
+        assert!(NamingStyle::ScreamingSnake.is_match("MAX_SIZE_2"));
+        assert!(!NamingStyle::ScreamingSnake.is_match("MAX_"));
+        assert!(!NamingStyle::ScreamingSnake.is_match("Max_Size"));
+    }
+
+    #[test]
+    fn should_parse_file_level() {
+        let code = "file(\"..controller..\")::loc should <= 500;";
+        let rules = parse(code).unwrap();
+
+        assert_eq!(RuleLevel::File, rules[0].level);
+        assert_eq!(Expr::PropsCall(vec!["loc".to_string()]), rules[0].expr);
+    }
+
+    #[test]
+    fn should_parse_empty_option() {
+        let code = "class(\"..controller..\")::name should endsWith \"Controller\" failOnEmpty;
+class(\"..legacy..\")::name should endsWith \"Service\" allowEmpty;
+class(\"..service..\")::name should endsWith \"Service\";";
+        let rules = parse(code).unwrap();
+
+        assert_eq!(Some(true), rules[0].fail_on_empty);
+        assert_eq!(Some(false), rules[1].fail_on_empty);
+        assert_eq!(None, rules[2].fail_on_empty);
+        assert_eq!(RuleAssert::Stringed("Controller".to_string()), rules[0].assert);
+    }
+
+    #[test]
+    fn should_keep_rule_origin() {
+        let code = "class::name contains \"Controller\";
+package(\".\")::file.len should < 200;";
+        let rules = parse(code).unwrap();
+
+        assert_eq!("class::name contains \"Controller\";", rules[0].origin);
+        assert_eq!("package(\".\")::file.len should < 200;", rules[1].origin);
+    }
+
     #[test]
     fn should_parse_struct() {
         let code = "struct::name contains \"Controller\";";
@@ -332,7 +434,33 @@ mod tests {
     fn should_parse_sized_assert() {
         let code = "class(\"..myapp..\")::function.vars.len should <= 20;";
         let vec = parse(code).unwrap();
-        assert_eq!(RuleAssert::Sized(20), vec[0].assert);
+        assert_eq!(RuleAssert::Number(Number::Integer(20)), vec[0].assert);
+    }
+
+    #[test]
+    fn should_parse_signed_and_fractional_assert() {
+        let code = "class(\"..myapp..\")::len should > -1;
+package(\"..domain..\")::instability should < 0.3;
+package(\"..domain..\")::coverage should <= 80%;
+package(\"..domain..\")::distance should >= -0.05;
+";
+        let vec = parse(code).unwrap();
+        assert_eq!(RuleAssert::Number(Number::Integer(-1)), vec[0].assert);
+        assert_eq!(RuleAssert::Number(Number::Decimal(0.3)), vec[1].assert);
+        assert_eq!(RuleAssert::Number(Number::Percentage(80.0)), vec[2].assert);
+        assert_eq!(RuleAssert::Number(Number::Decimal(-0.05)), vec[3].assert);
+        assert!(matches!(vec[1].assert, RuleAssert::Number(Number::Decimal(_))));
+        assert!(matches!(vec[2].assert, RuleAssert::Number(Number::Percentage(_))));
+    }
+
+    #[test]
+    fn should_compare_numbers_by_value() {
+        assert!(Number::Integer(1) > Number::Decimal(0.5));
+        assert!(Number::Decimal(0.7) < Number::Percentage(80.0));
+        assert_eq!(Number::Integer(1), Number::Decimal(1.0));
+        assert_eq!(Number::Percentage(80.0), Number::Decimal(0.8));
+        assert_ne!(Number::Integer(1), Number::Percentage(1.0));
+        assert_eq!("80%", format!("{}", Number::Percentage(80.0)));
     }
 
     #[test]
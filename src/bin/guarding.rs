@@ -1,8 +1,10 @@
 use std::fs;
 use std::path::PathBuf;
+use std::process;
 
 use clap::{AppSettings, Clap};
-use guarding::exec_guarding;
+use guarding::{exec_guarding_report, package_metrics, GuardingOptions};
+use guarding::git::ChangeScope;
 
 #[derive(Clap)]
 #[clap(version = "1.0", author = "Inherd Group <group@inherd.org>")]
@@ -16,16 +18,112 @@ struct Opts {
 
     #[clap(short, long, default_value = "guard.json")]
     output: String,
+
+    /// threads for rule evaluation, 0 uses all cores
+    #[clap(short, long, default_value = "1")]
+    threads: usize,
+
+    /// only fail on violations which are not in baseline file, create it with --update-baseline
+    #[clap(short, long)]
+    baseline: Option<String>,
+
+    /// record current violations into baseline file, fixed ones are removed
+    #[clap(long)]
+    update_baseline: bool,
+
+    /// fail rules which select nothing, like a typo in package identifier
+    #[clap(long)]
+    fail_on_empty: bool,
+
+    #[clap(subcommand)]
+    cmd: Option<SubCommand>,
+}
+
+#[derive(Clap)]
+enum SubCommand {
+    /// check rules, violations can be limited to files changed in git
+    Check(Check),
+    /// print coupling, instability and abstractness of every package
+    Metrics,
+}
+
+#[derive(Clap)]
+struct Check {
+    /// only report violations in files staged in git index, for pre-commit hooks. the
+    /// content is read from working tree, not from index
+    #[clap(long, conflicts_with = "since")]
+    changed: bool,
+
+    /// only report violations in files changed since the git revision
+    #[clap(long)]
+    since: Option<String>,
+}
+
+impl Check {
+    fn change_scope(&self) -> Option<ChangeScope> {
+        if self.changed {
+            return Some(ChangeScope::Staged);
+        }
+
+        self.since.as_ref().map(|rev| ChangeScope::Since(rev.clone()))
+    }
 }
 
 fn main() {
     let opts: Opts = Opts::parse();
 
     let buf = PathBuf::from(opts.path);
+    if let Some(SubCommand::Metrics) = opts.cmd {
+        print_metrics(buf);
+        return;
+    }
+
     let conf = PathBuf::from(opts.config);
     let content = fs::read_to_string(conf).unwrap();
 
-    let errors = exec_guarding(content, buf);
-    let content = serde_json::to_string_pretty(&errors).unwrap();
+    let options = GuardingOptions {
+        threads: opts.threads,
+        baseline: opts.baseline.map(PathBuf::from),
+        update_baseline: opts.update_baseline,
+        changes: match &opts.cmd {
+            Some(SubCommand::Check(check)) => check.change_scope(),
+            _ => None
+        },
+        fail_on_empty: opts.fail_on_empty,
+    };
+
+    if let Some(baseline) = &options.baseline {
+        if !options.update_baseline && !baseline.exists() {
+            println!("baseline {:?} not found, create it with --update-baseline", baseline);
+            process::exit(1);
+        }
+    }
+
+    let report = match exec_guarding_report(content, buf, &options) {
+        Ok(report) => report,
+        Err(e) => {
+            println!("{}", e);
+            process::exit(2);
+        }
+    };
+    let content = serde_json::to_string_pretty(&report).unwrap();
     let _ = fs::write(opts.output, content);
+
+    if !report.errors.is_empty() {
+        process::exit(1);
+    }
+}
+
+fn print_metrics(code_dir: PathBuf) {
+    println!("{:<48} {:>4} {:>4} {:>6} {:>6} {:>6}", "package", "Ca", "Ce", "I", "A", "D");
+    for metrics in package_metrics(code_dir) {
+        println!("{:<48} {:>4} {:>4} {:>6.2} {:>6.2} {:>6.2}",
+                 metrics.package,
+                 metrics.ca,
+                 metrics.ce,
+                 metrics.instability,
+                 metrics.abstractness,
+                 metrics.distance
+        );
+    }
 }
@@ -0,0 +1,146 @@
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use guarding_core::rule_executor::RuleErrorMsg;
+
+/// which changes of the local git repository are checked
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChangeScope {
+    /// files changed in the index, what `git commit` will record, for pre-commit hooks. only
+    /// the file list comes from the index, models are built from working tree content, so
+    /// unstaged edits of a staged file are checked too
+    Staged,
+    /// changes between a revision and the working tree
+    Since(String),
+}
+
+/// absolute paths of files changed in the repository which contains `dir`, deleted files
+/// are skipped. untracked files are changes since any revision, but not staged ones
+pub fn changed_files(dir: &Path, scope: &ChangeScope) -> io::Result<Vec<PathBuf>> {
+    let root = git(dir, &["rev-parse", "--show-toplevel"])?;
+    let root = PathBuf::from(root.trim());
+
+    let output = match scope {
+        ChangeScope::Staged => git(dir, &["diff", "--cached", "--name-only", "--diff-filter=d"])?,
+        ChangeScope::Since(rev) => {
+            let mut output = git(dir, &["diff", "--name-only", "--diff-filter=d", rev.as_str()])?;
+            output.push_str(&git(&root, &["ls-files", "--others", "--exclude-standard"])?);
+            output
+        }
+    };
+
+    let files = output.lines()
+        .filter(|line| !line.is_empty())
+        .filter_map(|line| root.join(line).canonicalize().ok())
+        .collect();
+
+    Ok(files)
+}
+
+/// keep violations of elements in `files`. errors without elements, like an empty scope or an
+/// invalid identifier, can't be related to a file, so they are always kept
+pub fn retain_changed(errors: Vec<RuleErrorMsg>, files: &[PathBuf]) -> Vec<RuleErrorMsg> {
+    let files: HashSet<&PathBuf> = files.iter().collect();
+
+    errors.into_iter()
+        .filter_map(|mut error| {
+            if error.items.is_empty() {
+                return Some(error);
+            }
+
+            error.items.retain(|item| {
+                match Path::new(&item.path).canonicalize() {
+                    Ok(path) => files.contains(&path),
+                    Err(_) => false
+                }
+            });
+
+            if error.items.is_empty() {
+                None
+            } else {
+                Some(error)
+            }
+        })
+        .collect()
+}
+
+fn git(dir: &Path, args: &[&str]) -> io::Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()?;
+
+    if !output.status.success() {
+        let msg = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(io::Error::other(format!("git {}: {}", args.join(" "), msg)));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use guarding_core::domain::code_file::CodeFile;
+    use guarding_core::rule_executor::{RuleErrorMsg, ViolationItem};
+
+    use crate::git::{changed_files, retain_changed, ChangeScope, git};
+
+    fn init_repo(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("guarding-git-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        git(&dir, &["init", "-q"]).unwrap();
+        fs::write(dir.join("Old.java"), "class Old {}").unwrap();
+        git(&dir, &["add", "."]).unwrap();
+        git(&dir, &["-c", "user.name=guarding", "-c", "user.email=guarding@inherd.org", "commit", "-q", "-m", "init"]).unwrap();
+
+        dir.canonicalize().unwrap()
+    }
+
+    #[test]
+    fn should_list_staged_and_since_files() {
+        let dir = init_repo("changed");
+        fs::write(dir.join("New.java"), "class New {}").unwrap();
+        fs::write(dir.join("Old.java"), "class Old { }").unwrap();
+        fs::write(dir.join("Untracked.java"), "class Untracked {}").unwrap();
+        git(&dir, &["add", "New.java"]).unwrap();
+
+        let staged = changed_files(&dir, &ChangeScope::Staged).unwrap();
+        assert_eq!(vec![dir.join("New.java")], staged);
+
+        let since = changed_files(&dir, &ChangeScope::Since("HEAD".to_string())).unwrap();
+        assert_eq!(vec![dir.join("New.java"), dir.join("Old.java"), dir.join("Untracked.java")], since);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn should_only_keep_items_in_changed_files() {
+        let dir = init_repo("retain");
+        fs::write(dir.join("New.java"), "class New {}").unwrap();
+
+        let old = CodeFile { path: format!("{}", dir.join("Old.java").display()), ..Default::default() };
+        let new = CodeFile { path: format!("{}", dir.join("New.java").display()), ..Default::default() };
+
+        let error = RuleErrorMsg {
+            items: vec![ViolationItem::file(&old), ViolationItem::file(&new)],
+            ..Default::default()
+        };
+        let errors = vec![error, RuleErrorMsg::default()];
+
+        let errors = retain_changed(errors, &[dir.join("New.java")]);
+        assert_eq!(2, errors.len());
+        assert_eq!(1, errors[0].items.len());
+        assert_eq!(new.path, errors[0].items[0].path);
+        assert!(errors[1].items.is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,31 +1,137 @@
 extern crate serde;
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
 
 use guarding_core::domain::code_file::CodeFile;
-use guarding_ident::ModelBuilder;
-use guarding_core::rule_executor::{RuleErrorMsg, RuleExecutor};
+use guarding_ident::{ModelBuilder, ModelStatistics};
+use guarding_core::metrics::PackageMetrics;
+use guarding_core::rule_executor::{Baseline, RuleErrorMsg, RuleExecutor, RuleReport};
 use guarding_parser::ast::GuardRule;
-use guarding_parser::parser;
+use guarding_parser::{errors, parser};
+
+use crate::git::ChangeScope;
+
+pub mod git;
+
+pub struct GuardingOptions {
+    /// threads for rule evaluation, `1` is sequential, `0` uses all cores
+    pub threads: usize,
+    /// only report violations which are not in this baseline file, all violations are
+    /// reported if it can't be loaded
+    pub baseline: Option<PathBuf>,
+    /// write the baseline with current violations, fixed ones are removed. it's the only way
+    /// to create a missing baseline
+    pub update_baseline: bool,
+    /// only report violations in files changed according to git, the model is still built
+    /// from all files, so that cross-file rules stay correct
+    pub changes: Option<ChangeScope>,
+    /// fail rules whose scope selects nothing, unless the rule ends with `allowEmpty`
+    pub fail_on_empty: bool,
+}
+
+impl Default for GuardingOptions {
+    fn default() -> Self {
+        GuardingOptions {
+            threads: 1,
+            baseline: None,
+            update_baseline: false,
+            changes: None,
+            fail_on_empty: false,
+        }
+    }
+}
+
+/// Result of a run, with passed rules and the statistics of parsed models.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct GuardingReport {
+    pub models: ModelStatistics,
+    /// one report for every rule
+    pub rules: Vec<RuleReport>,
+    pub errors: Vec<RuleErrorMsg>,
+}
 
 pub fn exec_guarding(rule_content: String, code_dir: PathBuf) -> Vec<RuleErrorMsg> {
-    match parser::parse(rule_content.as_str()) {
+    exec_guarding_with_options(rule_content, code_dir, &GuardingOptions::default())
+}
+
+pub fn exec_guarding_with_options(rule_content: String, code_dir: PathBuf, options: &GuardingOptions) -> Vec<RuleErrorMsg> {
+    match exec_guarding_report(rule_content, code_dir, options) {
+        Ok(report) => report.errors,
         Err(e) => {
             println!("{}", e);
             vec![]
-        },
-        Ok(rules) => {
-            let models = ModelBuilder::build_models_by_dir(code_dir);
-            exec(rules, models)
         }
     }
 }
 
-fn exec(rules: Vec<GuardRule>, models: Vec<CodeFile>) -> Vec<RuleErrorMsg> {
-    let mut executor = RuleExecutor::new(models, rules);
+/// run rules with a report of every rule, rules which can't be parsed are an error
+pub fn exec_guarding_report(rule_content: String, code_dir: PathBuf, options: &GuardingOptions) -> errors::Result<GuardingReport> {
+    let rules = parser::parse(rule_content.as_str())?;
+
+    let (models, statistics) = ModelBuilder::build_models_with_statistics(code_dir.clone());
+    let (mut errors, mut reports) = exec(rules.clone(), models, options);
+
+    if let Some(path) = &options.baseline {
+        errors = apply_baseline(path, &code_dir, &rules, errors, options.update_baseline);
+    }
+
+    if let Some(scope) = &options.changes {
+        errors = retain_changed(&code_dir, scope, errors);
+    }
+
+    for report in reports.iter_mut() {
+        let count = errors.iter().filter(|err| err.rule_index == report.rule_index).count();
+        report.set_errors(count);
+    }
+
+    Ok(GuardingReport {
+        models: statistics,
+        rules: reports,
+        errors,
+    })
+}
+
+pub fn package_metrics(code_dir: PathBuf) -> Vec<PackageMetrics> {
+    let models = ModelBuilder::build_models_by_dir(code_dir);
+    PackageMetrics::from_models(&models)
+}
+
+fn retain_changed(code_dir: &Path, scope: &ChangeScope, errors: Vec<RuleErrorMsg>) -> Vec<RuleErrorMsg> {
+    match git::changed_files(code_dir, scope) {
+        Ok(files) => git::retain_changed(errors, &files),
+        Err(e) => {
+            println!("list changed files error: {}", e);
+            errors
+        }
+    }
+}
+
+fn apply_baseline(path: &Path, code_dir: &Path, rules: &[GuardRule], errors: Vec<RuleErrorMsg>, update: bool) -> Vec<RuleErrorMsg> {
+    if update {
+        if let Err(e) = Baseline::from_errors(rules, &errors, code_dir).save(path) {
+            println!("save baseline {:?} error: {}", path, e);
+        }
+        return vec![];
+    }
+
+    match Baseline::load(path) {
+        Ok(baseline) => baseline.filter_new(rules, errors, code_dir),
+        Err(e) => {
+            println!("load baseline {:?} error: {}", path, e);
+            errors
+        }
+    }
+}
+
+fn exec(rules: Vec<GuardRule>, models: Vec<CodeFile>, options: &GuardingOptions) -> (Vec<RuleErrorMsg>, Vec<RuleReport>) {
+    let mut executor = RuleExecutor::new(models, rules)
+        .with_threads(options.threads)
+        .with_fail_on_empty(options.fail_on_empty);
     executor.run();
 
-    return executor.errors;
+    (executor.errors, executor.reports)
 }
 
 
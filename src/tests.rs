@@ -1,8 +1,17 @@
 use std::fs;
 use std::path::PathBuf;
+use std::process::Command;
 
+use guarding_core::domain::code_file::CodeFile;
+use guarding_core::rule_executor::{ElementKind, RuleContext, RuleErrorMsg, RuleEvaluator, RuleExecutor};
 use guarding_core::rule_executor::rule_error::MismatchType;
-use crate::exec_guarding;
+use guarding_ident::ModelBuilder;
+use guarding_parser::ast::{Operator, RuleLevel};
+use guarding_parser::parser;
+use guarding_core::rule_executor::RuleStatus;
+
+use crate::git::ChangeScope;
+use crate::{exec_guarding, exec_guarding_report, exec_guarding_with_options, package_metrics, GuardingOptions};
 
 fn test_dir() -> PathBuf {
     PathBuf::from(env!("CARGO_MANIFEST_DIR"))
@@ -91,6 +100,16 @@ fn should_support_for_reside_in() {
     assert_eq!(0, errors.len());
 }
 
+#[test]
+fn should_fail_reside_in_without_package() {
+    let content = "class(implementation \"BaseParser\") resideIn \"....parser\";";
+    let report = exec_guarding_report(content.to_string(), test_dir(), &GuardingOptions::default()).unwrap();
+
+    assert_eq!(RuleStatus::Failed, report.rules[0].status);
+    assert_eq!(1, report.errors.len());
+    assert!(report.errors[0].msg.contains("package identifier"));
+}
+
 #[test]
 fn should_support_for_not_reside_in() {
     let content = "class(implementation \"BaseParser\") not resideIn package(\"....parser2\");";
@@ -133,5 +152,292 @@ fn should_support_for_accessed() {
     let errors = exec_guarding(content.to_string(), test_dir());
 
     assert_eq!(1, errors.len());
-    assert!(errors[0].items[0].contains("MyDictionary.java"))
+    let item = &errors[0].items[0];
+    assert_eq!(ElementKind::File, item.kind);
+    assert!(item.path.ends_with("MyDictionary.java"));
+    assert_eq!(Some("java.util.Map".to_string()), item.import);
+}
+
+#[test]
+fn should_treat_wildcard_import_as_package_access() {
+    let content = "class(\"java.awt.Color\") only accessed([\"com.phodal.pepper.refactor.staticclass\"]);";
+    let errors = exec_guarding(content.to_string(), test_dir());
+
+    assert_eq!(1, errors.len());
+    let item = &errors[0].items[0];
+    assert!(item.path.ends_with("MockNewClass.java"));
+    assert_eq!(Some("java.awt.*".to_string()), item.import);
+}
+
+#[test]
+fn should_not_depend_on_rule_order() {
+    let rules = vec![
+        "class(implementation \"BaseParser\")::name should endsWith \"Parser2\";",
+        "class(\".\")::len should < 25;",
+        "class(\".\")::len should > 20;",
+        "class(implementation \"BaseParser\") resideIn package(\"....parser2\");",
+        "package(\".\")::file.len should = 27;",
+        "class(\"java.util.Map\") only accessed([\"com.phodal.pepper.refactor.staticclass\"]);",
+    ];
+
+    let run = |ordered: &Vec<&str>| {
+        let content = ordered.join("\n");
+        let errors = exec_guarding(content, test_dir());
+        let mut results: Vec<String> = errors.iter()
+            .map(|err| format!("{} => {} {:?}", ordered[err.rule_index], err.msg, err.items))
+            .collect();
+        results.sort();
+        results
+    };
+
+    let expected = run(&rules);
+    assert_eq!(4, expected.len());
+
+    let mut shuffled = rules.clone();
+    shuffled.reverse();
+    assert_eq!(expected, run(&shuffled));
+
+    for shift in 1..rules.len() {
+        let mut rotated = rules.clone();
+        rotated.rotate_left(shift);
+        assert_eq!(expected, run(&rotated));
+    }
+}
+
+struct AlwaysFailEvaluator;
+
+impl RuleEvaluator for AlwaysFailEvaluator {
+    fn evaluate(&self, ctx: &mut RuleContext, _models: &[CodeFile]) {
+        ctx.errors.push(RuleErrorMsg {
+            rule_index: ctx.index,
+            msg: format!("classes: {}", ctx.filtered_classes.len()),
+            ..Default::default()
+        });
+    }
+}
+
+#[test]
+fn should_support_custom_evaluator() {
+    let rules = parser::parse("class(\".\")::comment should contains \"TODO\";").unwrap();
+    let models = ModelBuilder::build_models_by_dir(test_dir());
+
+    let mut executor = RuleExecutor::new(models.clone(), rules.clone());
+    executor.run();
+    assert_eq!(0, executor.errors.len());
+
+    let mut executor = RuleExecutor::new(models, rules);
+    executor.register(RuleLevel::Class, "comment", Operator::Contains, AlwaysFailEvaluator);
+    executor.run();
+
+    assert_eq!(1, executor.errors.len());
+    assert!(executor.errors[0].msg.starts_with("classes: "));
+}
+
+#[test]
+fn should_keep_rule_order_when_run_in_parallel() {
+    let content = "class(implementation \"BaseParser\")::name should endsWith \"Parser2\";
+class(\".\")::len should > 30;
+class(implementation \"BaseParser\") resideIn package(\"....parser2\");
+package(\".\")::file.len should = 27;
+class(\"java.util.Map\") only accessed([\"com.phodal.pepper.refactor.staticclass\"]);";
+
+    let sequential = exec_guarding(content.to_string(), test_dir());
+    let options = GuardingOptions { threads: 4, ..Default::default() };
+    let parallel = exec_guarding_with_options(content.to_string(), test_dir(), &options);
+
+    assert_eq!(5, sequential.len());
+    assert_eq!(sequential, parallel);
+
+    let indexes: Vec<usize> = parallel.iter().map(|err| err.rule_index).collect();
+    assert_eq!(vec![0, 1, 2, 3, 4], indexes);
+}
+
+#[test]
+fn should_only_report_violations_not_in_baseline() {
+    let baseline = std::env::temp_dir().join(format!("guarding-baseline-{}.json", std::process::id()));
+    let _ = fs::remove_file(&baseline);
+
+    let mut options = GuardingOptions { baseline: Some(baseline.clone()), ..Default::default() };
+    let content = "class(implementation \"BaseParser\")::name should endsWith \"Parser2\";";
+
+    let errors = exec_guarding_with_options(content.to_string(), test_dir(), &options);
+    assert_eq!(1, errors.len());
+    assert!(!baseline.exists());
+
+    options.update_baseline = true;
+    let errors = exec_guarding_with_options(content.to_string(), test_dir(), &options);
+    assert_eq!(0, errors.len());
+    assert!(baseline.exists());
+    options.update_baseline = false;
+
+    let errors = exec_guarding_with_options(content.to_string(), test_dir(), &options);
+    assert_eq!(0, errors.len());
+
+    let content = format!("{}\nclass(implementation \"BaseParser\")::name should startsWith \"Json\";", content);
+    let errors = exec_guarding_with_options(content.clone(), test_dir(), &options);
+    assert_eq!(1, errors.len());
+    assert_eq!(1, errors[0].rule_index);
+
+    options.update_baseline = true;
+    exec_guarding_with_options(content.clone(), test_dir(), &options);
+    options.update_baseline = false;
+    let errors = exec_guarding_with_options(content, test_dir(), &options);
+    assert_eq!(0, errors.len());
+
+    let _ = fs::remove_file(&baseline);
+}
+
+#[test]
+fn should_support_package_metrics() {
+    let metrics = package_metrics(test_dir());
+    let parser = metrics.iter()
+        .find(|m| m.package == "com.phodal.pepper.refactor.parser")
+        .unwrap();
+    assert_eq!(3, parser.classes);
+    assert_eq!(1, parser.abstract_classes);
+
+    let content = "package(\".\")::distance should <= 1;";
+    let errors = exec_guarding(content.to_string(), test_dir());
+    assert_eq!(0, errors.len());
+
+    let content = "package(\"com.phodal.pepper.refactor.parser\")::abstractness should > 0.5;";
+    let errors = exec_guarding(content.to_string(), test_dir());
+    assert_eq!(1, errors.len());
+    assert_eq!(MismatchType::Metric, errors[0].mismatch_type);
+    assert_eq!(ElementKind::Package, errors[0].items[0].kind);
+    assert_eq!("com.phodal.pepper.refactor.parser", errors[0].items[0].name);
+}
+
+#[test]
+fn should_support_function_complexity() {
+    let content = "function(\".\")::complexity should <= 15;";
+    let errors = exec_guarding(content.to_string(), test_dir());
+    assert_eq!(0, errors.len());
+
+    let content = "function(\"com.phodal.pepper.refactor.parser\")::complexity should < 1;";
+    let errors = exec_guarding(content.to_string(), test_dir());
+    assert_eq!(1, errors.len());
+    assert_eq!(ElementKind::Function, errors[0].items[0].kind);
+    assert!(errors[0].items[0].name.starts_with("com.phodal.pepper.refactor.parser."));
+}
+
+#[test]
+fn should_support_size_metrics() {
+    let content = "file(\".\")::loc should <= 200;
+file(\".\")::lines should >= 1;
+class(\".\")::lines should < 200;
+class(\".\")::function.len should <= 20;
+class(\".\")::field.len should <= 20;
+function(\".\")::lines should <= 50;";
+    let errors = exec_guarding(content.to_string(), test_dir());
+    assert_eq!(0, errors.len());
+
+    let content = "file(\"com.phodal.pepper.refactor.parser\")::loc should < 3;";
+    let errors = exec_guarding(content.to_string(), test_dir());
+    assert_eq!(1, errors.len());
+    assert_eq!(3, errors[0].items.len());
+    assert_eq!(ElementKind::File, errors[0].items[0].kind);
+}
+
+#[test]
+fn should_support_naming_style() {
+    let content = "class(\".\")::name should be PascalCase;
+class(\".\")::function.name should be camelCase;
+class(\".\")::constant.name should be SCREAMING_SNAKE;
+file(\".\")::name should be PascalCase;";
+    let errors = exec_guarding(content.to_string(), test_dir());
+    assert_eq!(0, errors.len());
+
+    let content = "class(\"com.phodal.pepper.refactor.parser\")::name should be snake_case;";
+    let errors = exec_guarding(content.to_string(), test_dir());
+    assert_eq!(1, errors.len());
+    assert_eq!(2, errors[0].items.len());
+    assert_eq!("snake_case", errors[0].expected);
+    assert!(errors[0].msg.contains("JsonParser"));
+}
+
+#[test]
+fn should_report_every_rule() {
+    let content = "class(\".\")::len should > 20;
+class(implementation \"BaseParser\")::name should endsWith \"Parser2\";
+class(\"..nothing..\")::name should endsWith \"Parser\";
+class(\".\")::comment should contains \"TODO\";";
+    let report = exec_guarding_report(content.to_string(), test_dir(), &GuardingOptions::default()).unwrap();
+
+    let status: Vec<RuleStatus> = report.rules.iter().map(|rule| rule.status).collect();
+    assert_eq!(vec![RuleStatus::Passed, RuleStatus::Failed, RuleStatus::Empty, RuleStatus::Unsupported], status);
+
+    assert_eq!(2, report.rules[1].selected);
+    assert_eq!(2, report.rules[1].asserted);
+    assert_eq!(1, report.rules[1].errors);
+    assert_eq!("class(implementation \"BaseParser\")::name should endsWith \"Parser2\";", report.rules[1].id);
+
+    assert_eq!(26, report.models.files);
+    assert_eq!(Some(&26), report.models.languages.get("java"));
+    assert_eq!(1, report.errors.len());
+}
+
+#[test]
+fn should_fail_on_empty_rule() {
+    let content = "class(\"..controler..\")::name should endsWith \"Controller\";
+class(\"..legacy..\")::name should endsWith \"Controller\" allowEmpty;
+class(\".\")::len should > 20;";
+    let options = GuardingOptions { fail_on_empty: true, ..Default::default() };
+    let report = exec_guarding_report(content.to_string(), test_dir(), &options).unwrap();
+
+    let status: Vec<RuleStatus> = report.rules.iter().map(|rule| rule.status).collect();
+    assert_eq!(vec![RuleStatus::Failed, RuleStatus::Empty, RuleStatus::Passed], status);
+
+    assert_eq!(1, report.errors.len());
+    assert_eq!(MismatchType::Empty, report.errors[0].mismatch_type);
+    assert_eq!(0, report.errors[0].rule_index);
+}
+
+#[test]
+fn should_fail_on_empty_by_rule_option() {
+    let content = "class(\"..controler..\")::name should endsWith \"Controller\" failOnEmpty;
+class(\"..legacy..\")::name should endsWith \"Controller\";";
+    let report = exec_guarding_report(content.to_string(), test_dir(), &GuardingOptions::default()).unwrap();
+
+    let status: Vec<RuleStatus> = report.rules.iter().map(|rule| rule.status).collect();
+    assert_eq!(vec![RuleStatus::Failed, RuleStatus::Empty], status);
+    assert_eq!(1, report.errors.len());
+}
+
+#[test]
+fn should_fail_on_empty_rule_for_changed_files() {
+    let dir = std::env::temp_dir().join(format!("guarding-changed-empty-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("Old.java"), "package com.phodal;\nclass Old {}").unwrap();
+
+    let git = |args: &[&str]| {
+        let status = Command::new("git").arg("-C").arg(&dir).args(args).status().unwrap();
+        assert!(status.success());
+    };
+    git(&["init", "-q"]);
+    git(&["add", "."]);
+    git(&["-c", "user.name=guarding", "-c", "user.email=guarding@inherd.org", "commit", "-q", "-m", "init"]);
+
+    let content = "class(\"..controler..\")::name should endsWith \"Controller\";
+class(\".\")::name should endsWith \"Controller\";";
+    let options = GuardingOptions {
+        fail_on_empty: true,
+        changes: Some(ChangeScope::Since("HEAD".to_string())),
+        ..Default::default()
+    };
+    let report = exec_guarding_report(content.to_string(), dir.clone(), &options).unwrap();
+
+    let status: Vec<RuleStatus> = report.rules.iter().map(|rule| rule.status).collect();
+    assert_eq!(vec![RuleStatus::Failed, RuleStatus::Passed], status);
+    assert_eq!(1, report.errors.len());
+    assert_eq!(MismatchType::Empty, report.errors[0].mismatch_type);
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn should_return_error_for_invalid_rule() {
+    let report = exec_guarding_report("class(\".\")::name should;".to_string(), test_dir(), &GuardingOptions::default());
+    assert!(report.is_err());
 }